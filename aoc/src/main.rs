@@ -1,54 +1,153 @@
+mod registry;
+mod scaffold;
+
+use registry::{SolveFn, DEFAULT_YEAR, OPERATORS, SOLUTIONS, VERBOSE};
+
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        [] => {
+            run(SOLUTIONS.iter().collect());
+            Ok(())
+        }
+        ["run", selector] => {
+            registry::parse_selector(selector).map(|(year, day)| run(select(year, day, false)))
+        }
+        ["run", selector, "-v" | "--verbose"] => {
+            registry::parse_selector(selector).map(|(year, day)| run(select(year, day, true)))
+        }
+        ["run", selector, "--operators", list] => registry::parse_selector(selector)
+            .and_then(|(year, day)| run_operators(year, day, list)),
+        ["new", selector] => {
+            parse_new_selector(selector).and_then(|(year, day)| scaffold::new_day(year, day))
+        }
+        _ => {
+            print_usage();
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_usage() {
+    eprintln!("usage:");
+    eprintln!("    aoc                        run all years and days");
+    eprintln!("    aoc run <year>[:<day>]     run a year or a single day");
+    eprintln!("        -v, --verbose          annotated output, for days that support it");
+    eprintln!("        --operators <list>     a custom operator list such as +,*,||, for day 7");
+    eprintln!("    aoc new [<year>:]<day>     generate a new day crate from the template");
+}
+
+fn select(year: u32, day: Option<u32>, verbose: bool) -> Vec<&'static ((u32, u32), SolveFn)> {
+    SOLUTIONS
+        .iter()
+        .filter(|((y, d), _)| *y == year && day.is_none_or(|day| day == *d))
+        .map(
+            |entry| match VERBOSE.iter().find(|(key, _)| verbose && *key == entry.0) {
+                Some(verbose_entry) => verbose_entry,
+                None => entry,
+            },
+        )
+        .collect()
+}
+
+// a bare day refers to the default year
+fn parse_new_selector(s: &str) -> Result<(u32, u32), aoc_common::AocError> {
+    if s.contains(':') {
+        match registry::parse_selector(s)? {
+            (year, Some(day)) => Ok((year, day)),
+            (_, None) => Err(aoc_common::AocError::InvalidInput),
+        }
+    } else {
+        Ok((DEFAULT_YEAR, s.trim().parse()?))
+    }
+}
+
+fn run(mut selected: Vec<&((u32, u32), SolveFn)>) {
+    selected.sort_by_key(|(key, _)| *key);
+
+    if selected.is_empty() {
+        println!("No solutions registered for this selection");
+        return;
+    }
+
     rayon::ThreadPoolBuilder::new()
         .num_threads(16)
         .build_global()
         .unwrap();
 
-    let func_input: [(fn(&str) -> aoc_common::AocResult, String); 14] = [
-        (d01::solve, read_input("./inputs/d01")),
-        (d02::solve, read_input("./inputs/d02")),
-        (d03::solve, read_input("./inputs/d03")),
-        (d04::solve, read_input("./inputs/d04")),
-        (d05::solve, read_input("./inputs/d05")),
-        (d06::solve, read_input("./inputs/d06")),
-        (d07::solve, read_input("./inputs/d07")),
-        (d08::solve, read_input("./inputs/d08")),
-        (d09::solve, read_input("./inputs/d09")),
-        (d10::solve, read_input("./inputs/d10")),
-        (d11::solve, read_input("./inputs/d11")),
-        (d12::solve, read_input("./inputs/d12")),
-        (d13::solve, read_input("./inputs/d13")),
-        (d14::solve, read_input("./inputs/d14")),
-    ];
-
-    let mut solutions: Vec<aoc_common::AocResult> = Vec::with_capacity(func_input.len());
-    for _ in 0..func_input.len() {
-        solutions.push(Ok("".into()));
+    // a missing input only fails its own day
+    let mut func_input = Vec::with_capacity(selected.len());
+    let mut solutions: Vec<aoc_common::AocResult> = Vec::with_capacity(selected.len());
+    for &((year, day), f) in selected {
+        match read_input(&registry::input_path(year, day)) {
+            Ok(input) => {
+                func_input.push(((year, day), Some((f, input))));
+                solutions.push(Ok("".into()));
+            }
+            Err(e) => {
+                func_input.push(((year, day), None));
+                solutions.push(Err(e.into()));
+            }
+        }
     }
 
     let t = std::time::Instant::now();
 
     rayon::scope(|s| {
         let mut sols: &mut [aoc_common::AocResult] = &mut solutions;
-        for (f, input) in func_input.iter() {
+        for (_, f_input) in func_input.iter() {
             let (single, rest) = sols.split_at_mut(1);
-            s.spawn(move |_| single[0] = f(input));
+            if let Some((f, input)) = f_input {
+                s.spawn(move |_| single[0] = f(input));
+            }
             sols = rest;
         }
     });
 
     let dt = t.elapsed();
 
-    for (i, solution) in solutions.iter().enumerate() {
+    for (((year, day), _), solution) in func_input.iter().zip(solutions.iter()) {
         match solution {
             Ok(s) => println!("{}", s),
-            Err(e) => println!("Error in day {:02}: {}", i + 1, e),
+            Err(e) => println!("Error in {} day {:02}: {}", year, day, e),
         }
     }
 
     println!("Elapsed time for all solutions: {:?}", dt);
 }
 
-fn read_input(path: &str) -> String {
-    std::fs::read_to_string(path).unwrap()
+fn run_operators(year: u32, day: Option<u32>, list: &str) -> Result<(), aoc_common::AocError> {
+    let &((year, day), f) = OPERATORS
+        .iter()
+        .find(|((y, d), _)| *y == year && Some(*d) == day)
+        .ok_or_else(|| {
+            aoc_common::AocError::InvalidInputReason(
+                "--operators needs a single day that supports it".to_string(),
+            )
+        })?;
+
+    let input = read_input(&registry::input_path(year, day))?;
+
+    let t = std::time::Instant::now();
+    let solution = f(&input, list);
+    let dt = t.elapsed();
+
+    match solution {
+        Ok(s) => println!("{}", s),
+        Err(e) => println!("Error in {} day {:02}: {}", year, day, e),
+    }
+    println!("Elapsed time for all solutions: {:?}", dt);
+
+    Ok(())
+}
+
+fn read_input(path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(path)
 }
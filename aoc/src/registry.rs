@@ -0,0 +1,61 @@
+pub type SolveFn = fn(&str) -> aoc_common::AocResult;
+pub type OperatorsFn = fn(&str, &str) -> aoc_common::AocResult;
+
+pub const DEFAULT_YEAR: u32 = 2024;
+
+// ((year, day), solve), extended by `aoc new [<year>:]<day>`
+pub const SOLUTIONS: &[((u32, u32), SolveFn)] = &[
+    ((2024, 1), d01::solve),
+    ((2024, 2), d02::solve),
+    ((2024, 3), d03::solve),
+    ((2024, 4), d04::solve),
+    ((2024, 5), d05::solve),
+    ((2024, 6), d06::solve),
+    ((2024, 7), d07::solve),
+    ((2024, 8), d08::solve),
+    ((2024, 9), d09::solve),
+    ((2024, 10), d10::solve),
+    ((2024, 11), d11::solve),
+    ((2024, 12), d12::solve),
+    ((2024, 13), d13::solve),
+    ((2024, 14), d14::solve),
+];
+
+// annotated output for `aoc run <year>:<day> --verbose`, days without an entry fall back to solve
+pub const VERBOSE: &[((u32, u32), SolveFn)] = &[
+    ((2024, 3), d03::explain),
+    ((2024, 4), d04::explain),
+    ((2024, 5), d05::explain),
+    ((2024, 7), d07::explain),
+    ((2024, 8), d08::explain),
+];
+
+// a custom operator list for `aoc run <year>:<day> --operators <list>`
+pub const OPERATORS: &[((u32, u32), OperatorsFn)] = &[((2024, 7), d07::solve_operators)];
+
+// the 2024 crates predate multi-year support and keep their short names
+pub fn crate_name(year: u32, day: u32) -> String {
+    if year == DEFAULT_YEAR {
+        format!("d{:02}", day)
+    } else {
+        format!("y{}_d{:02}", year, day)
+    }
+}
+
+pub fn input_path(year: u32, day: u32) -> String {
+    format!("./inputs/{}/d{:02}", year, day)
+}
+
+// "2024" -> (2024, None), "2024:6" -> (2024, Some(6))
+pub fn parse_selector(s: &str) -> Result<(u32, Option<u32>), aoc_common::AocError> {
+    let (year, day) = match s.split_once(':') {
+        Some((year, day)) => (year.trim().parse()?, Some(day.trim().parse()?)),
+        None => (s.trim().parse()?, None),
+    };
+
+    if matches!(day, Some(d) if d == 0 || d > 25) {
+        return Err(aoc_common::AocError::InvalidInput);
+    }
+
+    Ok((year, day))
+}
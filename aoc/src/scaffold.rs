@@ -0,0 +1,190 @@
+use std::path::Path;
+
+use aoc_common::AocError;
+
+use crate::registry;
+
+const WORKSPACE_MANIFEST: &str = "./Cargo.toml";
+const RUNNER_MANIFEST: &str = "./aoc/Cargo.toml";
+const RUNNER_REGISTRY: &str = "./aoc/src/registry.rs";
+
+// must be called from the workspace root, like the runner itself
+pub fn new_day(year: u32, day: u32) -> Result<(), AocError> {
+    if day == 0 || day > 25 {
+        return Err(AocError::InvalidInput);
+    }
+
+    let name = registry::crate_name(year, day);
+    let crate_dir = Path::new(".").join(&name);
+    if crate_dir.exists() {
+        return Err(already_exists(&crate_dir));
+    }
+
+    // compute every edit up front, so that a malformed file leaves the tree untouched
+    let workspace = std::fs::read_to_string(WORKSPACE_MANIFEST)?;
+    let workspace = insert_workspace_member(&workspace, &name)?;
+
+    let runner = std::fs::read_to_string(RUNNER_MANIFEST)?;
+    let runner = insert_after_last(
+        &runner,
+        is_day_dependency,
+        &format!("{} = {{ path = \"../{}\" }}", name, name),
+    )?;
+
+    let registry = std::fs::read_to_string(RUNNER_REGISTRY)?;
+    let registry = insert_registry_entry(
+        &registry,
+        &format!("    (({}, {}), {}::solve),", year, day, name),
+    )?;
+
+    std::fs::create_dir_all(crate_dir.join("src"))?;
+    std::fs::write(crate_dir.join("Cargo.toml"), crate_manifest(&name))?;
+    std::fs::write(crate_dir.join("src").join("lib.rs"), crate_lib(&name))?;
+
+    std::fs::write(WORKSPACE_MANIFEST, workspace)?;
+    std::fs::write(RUNNER_MANIFEST, runner)?;
+    std::fs::write(RUNNER_REGISTRY, registry)?;
+
+    let input_path = registry::input_path(year, day);
+    let input_path = Path::new(&input_path);
+    if let Some(dir) = input_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if !input_path.exists() {
+        std::fs::write(input_path, "")?;
+    }
+
+    println!("created {}", name);
+    Ok(())
+}
+
+fn already_exists(path: &Path) -> AocError {
+    AocError::Io(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    ))
+}
+
+fn is_day_dependency(line: &str) -> bool {
+    let line = line.trim();
+    let line = match line.split_once('_') {
+        Some((year, rest)) if year.starts_with('y') => rest,
+        _ => line,
+    };
+    line.len() > 3
+        && line.starts_with('d')
+        && line[1..3].chars().all(|c| c.is_ascii_digit())
+        && line.contains("path")
+}
+
+fn insert_workspace_member(manifest: &str, name: &str) -> Result<String, AocError> {
+    let mut lines = manifest.lines().collect::<Vec<_>>();
+
+    let start = lines
+        .iter()
+        .position(|l| l.trim() == "members = [")
+        .ok_or(AocError::InvalidInput)?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|l| l.trim() == "]")
+            .ok_or(AocError::InvalidInput)?;
+
+    let member = format!("    \"{}\",", name);
+    lines.insert(end, &member);
+
+    Ok(join_lines(&lines))
+}
+
+fn insert_registry_entry(registry: &str, entry: &str) -> Result<String, AocError> {
+    let mut lines = registry.lines().collect::<Vec<_>>();
+
+    let start = lines
+        .iter()
+        .position(|l| l.starts_with("pub const SOLUTIONS"))
+        .ok_or(AocError::InvalidInput)?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|l| l.trim() == "];")
+            .ok_or(AocError::InvalidInput)?;
+
+    lines.insert(end, entry);
+
+    Ok(join_lines(&lines))
+}
+
+fn insert_after_last<F>(text: &str, predicate: F, line: &str) -> Result<String, AocError>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut lines = text.lines().collect::<Vec<_>>();
+    let last = lines
+        .iter()
+        .rposition(|l| predicate(l))
+        .ok_or(AocError::InvalidInput)?;
+
+    lines.insert(last + 1, line);
+
+    Ok(join_lines(&lines))
+}
+
+fn join_lines(lines: &[&str]) -> String {
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+fn crate_manifest(name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+aoc_common = {{ path = "../aoc_common" }}
+rustc-hash = {{ workspace = true }}
+"#
+    )
+}
+
+fn crate_lib(name: &str) -> String {
+    format!(
+        r#"pub fn solve(input: &str) -> aoc_common::AocResult {{
+    let lines = parse_input(input)?;
+
+    let s1 = lines.len();
+    let s2 = 0;
+
+    Ok(format!("{name}/01 = {{}}, {name}/02 = {{}}", s1, s2))
+}}
+
+fn parse_input(input: &str) -> Result<Vec<&str>, aoc_common::AocError> {{
+    let lines = input
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {{
+        return Err(aoc_common::AocError::InvalidInput);
+    }}
+
+    Ok(lines)
+}}
+
+#[cfg(test)]
+mod tests {{
+    const EXAMPLE: &str = "";
+
+    #[test]
+    #[ignore = "fill in the example from the puzzle statement"]
+    fn example() {{
+        let result = super::solve(EXAMPLE).unwrap();
+        assert_eq!(result, "{name}/01 = 0, {name}/02 = 0");
+    }}
+}}
+"#
+    )
+}
@@ -3,14 +3,18 @@ pub type AocResult = Result<String, AocError>;
 #[derive(Debug)]
 pub enum AocError {
     InvalidInput,
+    InvalidInputReason(String),
     ParseIntError(std::num::ParseIntError),
+    Io(std::io::Error),
 }
 
 impl std::fmt::Display for AocError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             AocError::InvalidInput => write!(f, "Invalid input"),
+            AocError::InvalidInputReason(r) => write!(f, "Invalid input: {}", r),
             AocError::ParseIntError(p) => write!(f, "ParseIntError: {}", p),
+            AocError::Io(e) => write!(f, "IoError: {}", e),
         }
     }
 }
@@ -23,6 +27,12 @@ impl From<std::num::ParseIntError> for AocError {
     }
 }
 
+impl From<std::io::Error> for AocError {
+    fn from(e: std::io::Error) -> Self {
+        AocError::Io(e)
+    }
+}
+
 pub fn split_to_array<'a, const N: usize>(line: &'a str, pat: &str) -> Option<[&'a str; N]> {
     let mut result = [""; N];
     let mut splitted = line.split(pat);
@@ -44,3 +54,25 @@ pub fn split_whitespace_to_array<const N: usize>(line: &str) -> Option<[&str; N]
 
     Some(result)
 }
+
+// small deterministic generator for the benchmark and fuzz examples
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % (n as u64)) as usize
+    }
+}
@@ -76,9 +76,7 @@ fn find_first_last(value: i64, list: &[i64], on_equal_down: bool) -> Option<usiz
 
         if list[i1] < value {
             i0 = i1;
-        } else if list[i1] > value {
-            i2 = i1;
-        } else if on_equal_down {
+        } else if list[i1] > value || on_equal_down {
             i2 = i1;
         } else {
             i0 = i1;
@@ -98,17 +96,8 @@ fn find_first_last(value: i64, list: &[i64], on_equal_down: bool) -> Option<usiz
     }
 
     if on_equal_down {
-        for i in i0..=i2 {
-            if list[i] == value {
-                return Some(i);
-            }
-        }
+        (i0..=i2).find(|&i| list[i] == value)
     } else {
-        for i in (i0..=i2).rev() {
-            if list[i] == value {
-                return Some(i);
-            }
-        }
+        (i0..=i2).rev().find(|&i| list[i] == value)
     }
-    None
 }
@@ -0,0 +1,83 @@
+// times the char Parser against the ByteScanner on a generated corrupted dump
+//
+//     cargo run --release -p d03 --example d03_bench -- [megabytes]
+
+use aoc_common::XorShift;
+
+const FRAGMENTS: &[&str] = &[
+    "mul(",
+    "mul(12,34)",
+    "mul(5,678)",
+    "do()",
+    "don't()",
+    "mul[3,7]",
+    "mul ( 2 , 4 )",
+    "what()",
+    "from()",
+    "select(",
+    "#",
+    "'",
+    "<",
+    ">",
+    " ",
+    "%",
+    "^",
+    "~",
+    "&",
+    "@",
+    "who()",
+    "why()",
+];
+
+fn main() {
+    let megabytes = std::env::args()
+        .nth(1)
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(64);
+
+    let input = generate(megabytes << 20);
+    println!("input: {} bytes", input.len());
+
+    let t = std::time::Instant::now();
+    let expected = sums(d03::parse(&input));
+    let dt_parser = t.elapsed();
+
+    let t = std::time::Instant::now();
+    let result = sums(d03::ByteScanner::new(input.as_bytes()));
+    let dt_scanner = t.elapsed();
+
+    assert_eq!(expected, result, "scanner and parser disagree");
+
+    println!("Parser:      {:?} {:?}", dt_parser, expected);
+    println!("ByteScanner: {:?} {:?}", dt_scanner, result);
+    println!(
+        "speedup:     {:.1}x",
+        dt_parser.as_secs_f64() / dt_scanner.as_secs_f64()
+    );
+}
+
+fn sums<T>(items: T) -> (u64, u64)
+where
+    T: Iterator<Item = (bool, u64)>,
+{
+    let mut s1 = 0;
+    let mut s2 = 0;
+    for (enabled, product) in items {
+        s1 += product;
+        if enabled {
+            s2 += product;
+        }
+    }
+    (s1, s2)
+}
+
+fn generate(len: usize) -> String {
+    let mut rng = XorShift::new(0x2024_0003);
+    let mut result = String::with_capacity(len + 16);
+
+    while result.len() < len {
+        result.push_str(FRAGMENTS[rng.below(FRAGMENTS.len())]);
+    }
+
+    result
+}
@@ -0,0 +1,187 @@
+// compares the d03 parsers against a regex oracle on random and adversarial inputs, for
+// long runs beyond the fixed batch in the unit tests
+//
+//     cargo run --release -p d03 --example fuzz -- [iterations] [seed]
+
+use std::io::BufReader;
+
+use aoc_common::XorShift;
+
+const FRAGMENTS: &[&str] = &[
+    "mul(",
+    "mul",
+    "mmul(",
+    "mu",
+    "do()",
+    "don't()",
+    "do(",
+    "don't",
+    "dodon't()",
+    "ddo()",
+    "do",
+    "(",
+    ")",
+    ",",
+    "[",
+    "]",
+    " ",
+    "'",
+    "m",
+    "u",
+    "l",
+    "d",
+    "o",
+    "n",
+    "t",
+    "x",
+    "é",
+    "1",
+    "12",
+    "123",
+    "1234",
+    "0",
+    "007",
+];
+
+// inputs that broke a parser before, checked ahead of the random ones
+const ADVERSARIAL: &[&str] = &[
+    "do('t()mul(7,4)",
+    "don't()do('t()mul(7,4)",
+    "dodon't()mul(2,3)",
+    "ddon't()mul(2,3)",
+    "mmul(2,3)",
+    "mumul(2,3)",
+    "don'do()mul(2,3)",
+    "do(do()mul(2,3)",
+];
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let iterations = args
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(2_000_000);
+    let seed = args
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0x2024_0003);
+
+    let oracle = Oracle::new();
+    let mut rng = XorShift::new(seed);
+
+    let adversarial = ADVERSARIAL.iter().map(|s| s.to_string());
+    let random = (0..iterations).map(|_| random_input(&mut rng));
+
+    for (i, input) in adversarial.chain(random).enumerate() {
+        if diverges(&oracle, &input) {
+            let minimal = shrink(&oracle, input);
+            println!("divergence after {} iterations on {:?}", i + 1, minimal);
+            println!("    oracle      = {:?}", oracle.run(&minimal));
+            println!("    parser      = {:?}", parser(&minimal));
+            println!("    stream      = {:?}", stream(&minimal));
+            println!("    scanner     = {:?}", scanner(&minimal));
+            println!("    interpreter = {:?}", interpreter(&minimal));
+            std::process::exit(1);
+        }
+    }
+
+    println!(
+        "no divergence in {} iterations",
+        ADVERSARIAL.len() + iterations
+    );
+}
+
+fn random_input(rng: &mut XorShift) -> String {
+    let len = rng.below(40);
+    let mut result = String::new();
+
+    for _ in 0..len {
+        if rng.below(4) == 0 {
+            // well formed instructions keep the interesting paths busy
+            let a = rng.below(1000);
+            let b = rng.below(1000);
+            result.push_str(&format!("mul({},{})", a, b));
+        } else {
+            result.push_str(FRAGMENTS[rng.below(FRAGMENTS.len())]);
+        }
+    }
+
+    result
+}
+
+fn diverges(oracle: &Oracle, input: &str) -> bool {
+    let expected = oracle.run(input);
+    parser(input) != expected
+        || stream(input) != expected
+        || scanner(input) != expected
+        || interpreter(input) != expected
+}
+
+// greedily drops chars while the divergence persists
+fn shrink(oracle: &Oracle, input: String) -> String {
+    let mut chars = input.chars().collect::<Vec<_>>();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let mut candidate = chars.clone();
+        candidate.remove(i);
+        if diverges(oracle, &candidate.iter().collect::<String>()) {
+            chars = candidate;
+        } else {
+            i += 1;
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+fn parser(input: &str) -> Vec<(bool, u64)> {
+    d03::parse(input).collect()
+}
+
+fn scanner(input: &str) -> Vec<(bool, u64)> {
+    d03::ByteScanner::new(input.as_bytes()).collect()
+}
+
+fn stream(input: &str) -> Vec<(bool, u64)> {
+    d03::StreamParser::new(BufReader::new(input.as_bytes()))
+        .map(|r| r.unwrap())
+        .collect()
+}
+
+fn interpreter(input: &str) -> Vec<(bool, u64)> {
+    d03::InstructionSet::puzzle()
+        .evaluate(input)
+        .filter_map(|e| e.value.map(|v| (e.enabled, v)))
+        .collect()
+}
+
+struct Oracle {
+    re: regex::Regex,
+}
+
+impl Oracle {
+    fn new() -> Self {
+        let re = regex::Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)|do\(\)|don't\(\)").unwrap();
+        Self { re }
+    }
+
+    fn run(&self, input: &str) -> Vec<(bool, u64)> {
+        let mut enabled = true;
+        let mut result = Vec::new();
+
+        for m in self.re.captures_iter(input) {
+            match &m[0] {
+                "do()" => enabled = true,
+                "don't()" => enabled = false,
+                _ => {
+                    let a = m[1].parse::<u64>().unwrap();
+                    let b = m[2].parse::<u64>().unwrap();
+                    result.push((enabled, a * b));
+                }
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::Write;
+
+use crate::{InstructionSet, Operation};
+
+// longest snippet that is still reported as a near miss of mul(a,b)
+const NEAR_MISS_LEN: usize = 16;
+
+// annotated listing of every mul, the toggle that decided it, and ignored near misses
+pub fn explain(input: &str) -> aoc_common::AocResult {
+    let instructions = InstructionSet::puzzle();
+
+    let mut out = String::new();
+    let mut s1 = 0;
+    let mut s2 = 0;
+    let mut n_enabled = 0;
+    let mut n_disabled = 0;
+    let mut matched = Vec::new();
+    let mut last_toggle: Option<(usize, &str)> = None;
+
+    writeln!(out, "d03 instructions (byte offsets):").unwrap();
+
+    for event in instructions.evaluate(input) {
+        let Some(product) = event.value else {
+            last_toggle = Some((event.offset, &event.instruction.name));
+            continue;
+        };
+
+        debug_assert_eq!(event.instruction.operation, Operation::Product);
+        matched.push(event.offset);
+
+        s1 += product;
+        if event.enabled {
+            s2 += product;
+            n_enabled += 1;
+        } else {
+            n_disabled += 1;
+        }
+
+        let text = format!("mul({},{})", event.args[0], event.args[1]);
+        let state = if event.enabled { "enabled" } else { "disabled" };
+        let cause = match last_toggle {
+            Some((offset, name)) => format!("by {}() @ {}", name, offset),
+            None => "initially".to_string(),
+        };
+
+        writeln!(
+            out,
+            "  @ {:>6}  {:<14} = {:<8} {:<8} {}",
+            event.offset, text, product, state, cause
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "{} mul instructions, {} enabled, {} disabled",
+        n_enabled + n_disabled,
+        n_enabled,
+        n_disabled
+    )
+    .unwrap();
+
+    let near_misses = near_misses(input, &matched);
+    if near_misses.is_empty() {
+        writeln!(out, "no near misses").unwrap();
+    } else {
+        writeln!(out, "ignored near misses (digits shown as n):").unwrap();
+        for (shape, count, offset, snippet) in near_misses {
+            writeln!(
+                out,
+                "  {:<18} x{:<5} first @ {}: {}",
+                shape, count, offset, snippet
+            )
+            .unwrap();
+        }
+    }
+
+    write!(out, "d03/01 = {}, d03/02 = {}", s1, s2).unwrap();
+
+    Ok(out)
+}
+
+// (shape, count, first offset, first snippet) of every "mul" that did not parse but carries numbers
+fn near_misses<'a>(input: &'a str, matched: &[usize]) -> Vec<(String, usize, usize, &'a str)> {
+    let bytes = input.as_bytes();
+    let mut result: Vec<(String, usize, usize, &str)> = Vec::new();
+
+    for offset in memchr::memmem::find_iter(bytes, b"mul") {
+        if matched.binary_search(&offset).is_ok() {
+            continue;
+        }
+
+        let Some(snippet) = near_miss_snippet(&input[offset..]) else {
+            continue;
+        };
+
+        let shape = shape(snippet);
+        match result.iter_mut().find(|(s, _, _, _)| *s == shape) {
+            Some(entry) => entry.1 += 1,
+            None => result.push((shape, 1, offset, snippet)),
+        }
+    }
+
+    result
+}
+
+// "mul" up to and including the first closing bracket, if it contains a number
+fn near_miss_snippet(rest: &str) -> Option<&str> {
+    let end = rest
+        .char_indices()
+        .take_while(|&(i, _)| i < NEAR_MISS_LEN)
+        .skip(3)
+        .find(|&(_, c)| matches!(c, ')' | ']' | '}' | '>' | 'm'))
+        .filter(|&(_, c)| c != 'm')
+        .map(|(i, _)| i + 1)?;
+
+    let snippet = &rest[..end];
+    if snippet.bytes().any(|b| b.is_ascii_digit()) {
+        Some(snippet)
+    } else {
+        None
+    }
+}
+
+fn shape(snippet: &str) -> String {
+    let mut result = String::new();
+    let mut in_number = false;
+
+    for c in snippet.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                result.push('n');
+            }
+            in_number = true;
+        } else {
+            result.push(c);
+            in_number = false;
+        }
+    }
+
+    result
+}
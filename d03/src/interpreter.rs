@@ -0,0 +1,221 @@
+use aoc_common::AocError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Product,
+    Sum,
+    Enable,
+    Disable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub name: String,
+    pub arity: usize,
+    pub operation: Operation,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstructionSet {
+    instructions: Vec<Instruction>,
+    // instruction indices by the first byte of their name
+    by_first_byte: Vec<Vec<usize>>,
+    max_digits: usize,
+}
+
+// a syntactically complete instruction, offset is the byte offset of its name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub offset: usize,
+    pub instruction: usize,
+    pub args: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event<'a> {
+    pub offset: usize,
+    pub instruction: &'a Instruction,
+    pub args: Vec<u64>,
+    // state in effect after this instruction
+    pub enabled: bool,
+    // None for toggles and results that overflow
+    pub value: Option<u64>,
+    // the result does not fit a u64, possible once max_digits is raised
+    pub overflow: bool,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self {
+            instructions: Vec::new(),
+            by_first_byte: vec![Vec::new(); 256],
+            max_digits: 3,
+        }
+    }
+
+    // mul(a,b), do() and don't(), as in the puzzle
+    pub fn puzzle() -> Self {
+        let mut result = Self::new();
+        result.register("mul", 2, Operation::Product).unwrap();
+        result.register("do", 0, Operation::Enable).unwrap();
+        result.register("don't", 0, Operation::Disable).unwrap();
+        result
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        arity: usize,
+        operation: Operation,
+    ) -> Result<(), AocError> {
+        let valid_name = !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_graphic() && !b.is_ascii_digit() && !b"(),".contains(&b));
+        let valid_arity = match operation {
+            Operation::Product | Operation::Sum => arity > 0,
+            Operation::Enable | Operation::Disable => true,
+        };
+        let duplicate = self.instructions.iter().any(|i| i.name == name);
+
+        if !valid_name || !valid_arity || duplicate {
+            return Err(AocError::InvalidInput);
+        }
+
+        self.by_first_byte[name.as_bytes()[0] as usize].push(self.instructions.len());
+        self.instructions.push(Instruction {
+            name: name.to_string(),
+            arity,
+            operation,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_max_digits(&mut self, max_digits: usize) {
+        self.max_digits = max_digits.clamp(1, 19);
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn scan<'a>(&'a self, input: &'a str) -> impl Iterator<Item = Match> + 'a {
+        let bytes = input.as_bytes();
+        let mut offset = 0;
+
+        std::iter::from_fn(move || {
+            while offset < bytes.len() {
+                let start = offset;
+                offset += 1;
+
+                for &index in self.by_first_byte[bytes[start] as usize].iter() {
+                    if let Some((args, end)) = self.match_at(index, bytes, start) {
+                        offset = end;
+                        return Some(Match {
+                            offset: start,
+                            instruction: index,
+                            args,
+                        });
+                    }
+                }
+            }
+
+            None
+        })
+    }
+
+    pub fn evaluate<'a>(&'a self, input: &'a str) -> impl Iterator<Item = Event<'a>> + 'a {
+        let mut enabled = true;
+
+        self.scan(input).map(move |m| {
+            let instruction = &self.instructions[m.instruction];
+
+            let mut overflow = false;
+            let mut checked = |value: Option<u64>| {
+                overflow = value.is_none();
+                value
+            };
+
+            let value = match instruction.operation {
+                Operation::Product => checked(
+                    m.args
+                        .iter()
+                        .try_fold(1u64, |product, &arg| product.checked_mul(arg)),
+                ),
+                Operation::Sum => checked(
+                    m.args
+                        .iter()
+                        .try_fold(0u64, |sum, &arg| sum.checked_add(arg)),
+                ),
+                Operation::Enable => {
+                    enabled = true;
+                    None
+                }
+                Operation::Disable => {
+                    enabled = false;
+                    None
+                }
+            };
+
+            Event {
+                offset: m.offset,
+                instruction,
+                args: m.args,
+                enabled,
+                value,
+                overflow,
+            }
+        })
+    }
+
+    // (args, end offset) of instruction `index` starting at `start`
+    fn match_at(&self, index: usize, bytes: &[u8], start: usize) -> Option<(Vec<u64>, usize)> {
+        let instruction = &self.instructions[index];
+
+        let mut pos = start + instruction.name.len();
+        if !bytes[start..].starts_with(instruction.name.as_bytes()) || bytes.get(pos) != Some(&b'(')
+        {
+            return None;
+        }
+        pos += 1;
+
+        let mut args = Vec::with_capacity(instruction.arity);
+        for n in 0..instruction.arity {
+            if n > 0 {
+                if bytes.get(pos) != Some(&b',') {
+                    return None;
+                }
+                pos += 1;
+            }
+
+            let digits = bytes[pos..]
+                .iter()
+                .take(self.max_digits + 1)
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if digits == 0 || digits > self.max_digits {
+                return None;
+            }
+
+            let mut value = 0;
+            for &b in bytes[pos..(pos + digits)].iter() {
+                value = value * 10 + (b - b'0') as u64;
+            }
+            args.push(value);
+            pos += digits;
+        }
+
+        if bytes.get(pos) != Some(&b')') {
+            return None;
+        }
+
+        Some((args, pos + 1))
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
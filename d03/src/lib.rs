@@ -1,12 +1,20 @@
+mod explain;
+mod interpreter;
+mod scanner;
+mod stream;
+
+pub use explain::explain;
+pub use interpreter::{Event, Instruction, InstructionSet, Match, Operation};
+pub use scanner::ByteScanner;
+pub use stream::{solve_reader, StreamParser};
+
 pub fn solve(input: &str) -> aoc_common::AocResult {
     // let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
 
-    let parser = Parser::new(input.chars());
-
     let mut s1 = 0;
     let mut s2 = 0;
 
-    for (enabled, product) in parser {
+    for (enabled, product) in ByteScanner::new(input.as_bytes()) {
         s1 += product;
         if enabled {
             s2 += product;
@@ -16,6 +24,11 @@ pub fn solve(input: &str) -> aoc_common::AocResult {
     Ok(format!("d03/01 = {}, d03/02 = {}", s1, s2))
 }
 
+// char by char reference implementation, ByteScanner is the fast path
+pub fn parse(input: &str) -> impl Iterator<Item = (bool, u64)> + '_ {
+    Parser::new(input.chars())
+}
+
 struct Parser<T>
 where
     T: Iterator<Item = char>,
@@ -46,7 +59,8 @@ where
         let mut prefix = None;
 
         loop {
-            let token = consume_to_token(prefix, &mut self.input);
+            // the handed back char belongs to exactly one token attempt
+            let token = consume_to_token(prefix.take(), &mut self.input);
             match token {
                 Token::Do => {
                     self.enabled = true;
@@ -104,34 +118,30 @@ where
     }
 }
 
+const MAX_DIGITS: usize = 3;
+
+// (number, first char after it), a number has 1 to MAX_DIGITS digits
 fn parse_number<T>(chars: &mut T) -> (Option<u64>, Option<char>)
 where
     T: Iterator<Item = char>,
 {
     let mut result = 0;
-    let mut read = false;
+    let mut digits = 0;
 
-    for (i, c) in chars.enumerate() {
-        if i >= 4 {
-            return (None, Some(c));
-        }
-
-        read = true;
-        let n = c.to_digit(10).map(|n| n as u64);
-
-        match n {
-            Some(n) => result = result * 10 + n,
-            None => {
-                if i == 0 {
-                    return (None, Some(c));
-                } else {
-                    return (Some(result), Some(c));
-                }
+    for c in chars {
+        match c.to_digit(10) {
+            Some(n) if digits < MAX_DIGITS => {
+                result = result * 10 + n as u64;
+                digits += 1;
             }
+            // too many digits, c can not start a token so handing it back is harmless
+            Some(_) => return (None, Some(c)),
+            None if digits == 0 => return (None, Some(c)),
+            None => return (Some(result), Some(c)),
         }
     }
 
-    if !read {
+    if digits == 0 {
         (None, None)
     } else {
         (Some(result), None)
@@ -157,64 +167,183 @@ where
     let mut index = 0;
 
     let iter = match prefix {
-        Some(c) => std::iter::repeat(c).take(1).chain(chars),
-        None => std::iter::repeat(' ').take(0).chain(chars),
+        Some(c) => std::iter::repeat_n(c, 1).chain(chars),
+        None => std::iter::repeat_n(' ', 0).chain(chars),
     };
 
     for c in iter {
-        match current {
-            None => {
-                if c == mul_token[0] {
-                    current = Some(Token::Mul);
-                } else if c == do_token[0] {
-                    current = Some(Token::Do);
-                }
-                // first chars of don't is handled by do
-                index = 1;
-            }
-
-            Some(Token::Mul) => {
-                if c == mul_token[index] {
-                    index += 1;
-                } else {
-                    current = None;
-                }
-
-                if index == mul_token.len() {
-                    return Token::Mul;
-                }
-            }
+        // a char that breaks the current token may still start a new one (e.g. "mmul(")
+        let continued = match current {
+            Some(Token::Mul) => c == mul_token[index],
 
             Some(Token::Do) => {
                 if c == do_token[index] {
-                    index += 1;
-                } else if c == dont_token[index] {
+                    true
+                } else if index == 2 && c == dont_token[2] {
+                    // "do" is shared, don't only branches off at the 'n'
                     current = Some(Token::Dont);
-                    index += 1;
+                    true
                 } else {
-                    current = None;
-                }
-
-                if index == do_token.len() {
-                    return Token::Do;
+                    false
                 }
             }
 
-            Some(Token::Dont) => {
-                if c == dont_token[index] {
-                    index += 1;
-                } else {
-                    current = None;
-                }
+            Some(Token::Dont) => c == dont_token[index],
 
-                if index == dont_token.len() {
-                    return Token::Dont;
-                }
+            _ => false,
+        };
+
+        if continued {
+            index += 1;
+
+            match current {
+                Some(Token::Mul) if index == mul_token.len() => return Token::Mul,
+                Some(Token::Do) if index == do_token.len() => return Token::Do,
+                Some(Token::Dont) if index == dont_token.len() => return Token::Dont,
+                _ => (),
             }
 
-            _ => current = None,
+            continue;
         }
+
+        if c == mul_token[0] {
+            current = Some(Token::Mul);
+        } else if c == do_token[0] {
+            // first chars of don't is handled by do
+            current = Some(Token::Do);
+        } else {
+            current = None;
+        }
+        index = 1;
     }
 
     Token::Eof
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use aoc_common::XorShift;
+
+    // inputs that broke a parser before
+    const ADVERSARIAL: &[&str] = &[
+        "do('t()mul(7,4)",
+        "don't()do('t()mul(7,4)",
+        "dodon't()mul(2,3)",
+        "ddon't()mul(2,3)",
+        "mmul(2,3)",
+        "mumul(2,3)",
+        "don'do()mul(2,3)",
+        "do(do()mul(2,3)",
+    ];
+
+    const FRAGMENTS: &[&str] = &[
+        "mul(",
+        "mul",
+        "mmul(",
+        "mu",
+        "do()",
+        "don't()",
+        "do(",
+        "don't",
+        "dodon't()",
+        "ddo()",
+        "do",
+        "(",
+        ")",
+        ",",
+        " ",
+        "'",
+        "m",
+        "u",
+        "l",
+        "d",
+        "o",
+        "n",
+        "t",
+        "é",
+        "1",
+        "12",
+        "123",
+        "1234",
+        "007",
+    ];
+
+    fn oracle(input: &str) -> Vec<(bool, u64)> {
+        let re = regex::Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)|do\(\)|don't\(\)").unwrap();
+        let mut enabled = true;
+        let mut result = Vec::new();
+
+        for m in re.captures_iter(input) {
+            match &m[0] {
+                "do()" => enabled = true,
+                "don't()" => enabled = false,
+                _ => result.push((
+                    enabled,
+                    m[1].parse::<u64>().unwrap() * m[2].parse::<u64>().unwrap(),
+                )),
+            }
+        }
+
+        result
+    }
+
+    fn check(input: &str) {
+        let expected = oracle(input);
+
+        assert_eq!(
+            super::parse(input).collect::<Vec<_>>(),
+            expected,
+            "parse on {:?}",
+            input
+        );
+        assert_eq!(
+            super::StreamParser::new(BufReader::new(input.as_bytes()))
+                .map(|r| r.unwrap())
+                .collect::<Vec<_>>(),
+            expected,
+            "StreamParser on {:?}",
+            input
+        );
+        assert_eq!(
+            super::ByteScanner::new(input.as_bytes()).collect::<Vec<_>>(),
+            expected,
+            "ByteScanner on {:?}",
+            input
+        );
+        assert_eq!(
+            super::InstructionSet::puzzle()
+                .evaluate(input)
+                .filter_map(|e| e.value.map(|v| (e.enabled, v)))
+                .collect::<Vec<_>>(),
+            expected,
+            "InstructionSet on {:?}",
+            input
+        );
+    }
+
+    #[test]
+    fn adversarial_inputs_match_oracle() {
+        for input in ADVERSARIAL {
+            check(input);
+        }
+    }
+
+    #[test]
+    fn random_inputs_match_oracle() {
+        let mut rng = XorShift::new(0x2024_0003);
+
+        for _ in 0..5000 {
+            let mut input = String::new();
+            for _ in 0..rng.below(40) {
+                if rng.below(4) == 0 {
+                    input += &format!("mul({},{})", rng.below(1000), rng.below(1000));
+                } else {
+                    input += FRAGMENTS[rng.below(FRAGMENTS.len())];
+                }
+            }
+            check(&input);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::MAX_DIGITS;
+
+// byte level equivalent of Parser: memchr jumps to the next 'm' or 'd', which is then validated in place
+pub struct ByteScanner<'a> {
+    input: &'a [u8],
+    position: usize,
+    enabled: bool,
+}
+
+impl<'a> ByteScanner<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            position: 0,
+            enabled: true,
+        }
+    }
+}
+
+impl Iterator for ByteScanner<'_> {
+    type Item = (bool, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let offset = memchr::memchr2(b'm', b'd', &self.input[self.position..])?;
+            let start = self.position + offset;
+            let rest = &self.input[start..];
+
+            // no token can start inside a failed candidate, except at the byte that broke it,
+            // which is never an 'm' or 'd' past the first one, so advancing by one is enough
+            self.position = start + 1;
+
+            if rest.starts_with(b"mul(") {
+                if let Some((product, len)) = parse_mul_arguments(&rest[4..]) {
+                    self.position = start + 4 + len;
+                    return Some((self.enabled, product));
+                }
+            } else if rest.starts_with(b"do()") {
+                self.enabled = true;
+                self.position = start + 4;
+            } else if rest.starts_with(b"don't()") {
+                self.enabled = false;
+                self.position = start + 7;
+            }
+        }
+    }
+}
+
+// "a,b)" -> (a * b, consumed bytes)
+fn parse_mul_arguments(bytes: &[u8]) -> Option<(u64, usize)> {
+    let (a, len_a) = parse_number(bytes)?;
+    if bytes.get(len_a) != Some(&b',') {
+        return None;
+    }
+
+    let (b, len_b) = parse_number(&bytes[(len_a + 1)..])?;
+    let end = len_a + 1 + len_b;
+    if bytes.get(end) != Some(&b')') {
+        return None;
+    }
+
+    Some((a * b, end + 1))
+}
+
+fn parse_number(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut result = 0;
+    let mut digits = 0;
+
+    for &b in bytes.iter().take(MAX_DIGITS + 1) {
+        if !b.is_ascii_digit() {
+            break;
+        }
+        result = result * 10 + (b - b'0') as u64;
+        digits += 1;
+    }
+
+    if digits == 0 || digits > MAX_DIGITS {
+        None
+    } else {
+        Some((result, digits))
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::{BufRead, BufReader, Read};
+
+use crate::Parser;
+
+// yields (enabled, product) for every mul instruction of a stream, in constant memory
+pub struct StreamParser<R>
+where
+    R: BufRead,
+{
+    parser: Parser<Utf8Chars<R>>,
+    done: bool,
+}
+
+impl<R> StreamParser<R>
+where
+    R: BufRead,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            parser: Parser::new(Utf8Chars::new(reader)),
+            done: false,
+        }
+    }
+}
+
+impl<R> StreamParser<BufReader<R>>
+where
+    R: Read,
+{
+    pub fn from_read(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl<R> Iterator for StreamParser<R>
+where
+    R: BufRead,
+{
+    type Item = std::io::Result<(bool, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if let Some(item) = self.parser.next() {
+            return Some(Ok(item));
+        }
+
+        // the char iterator ends on the first io error, which is reported once here
+        self.done = true;
+        self.parser.input.error.take().map(Err)
+    }
+}
+
+pub fn solve_reader<R>(reader: R) -> aoc_common::AocResult
+where
+    R: BufRead,
+{
+    let mut s1 = 0;
+    let mut s2 = 0;
+
+    for item in StreamParser::new(reader) {
+        let (enabled, product) = item?;
+        s1 += product;
+        if enabled {
+            s2 += product;
+        }
+    }
+
+    Ok(format!("d03/01 = {}, d03/02 = {}", s1, s2))
+}
+
+// lossy utf-8 decoding, sequences may be split across buffer boundaries
+struct Utf8Chars<R>
+where
+    R: BufRead,
+{
+    reader: R,
+    error: Option<std::io::Error>,
+}
+
+impl<R> Utf8Chars<R>
+where
+    R: BufRead,
+{
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            error: None,
+        }
+    }
+
+    fn peek_byte(&mut self) -> Option<u8> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => return buf.first().copied(),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl<R> Iterator for Utf8Chars<R>
+where
+    R: BufRead,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lead = self.peek_byte()?;
+        self.reader.consume(1);
+
+        let (width, mut code) = match lead {
+            0x00..=0x7f => return Some(lead as char),
+            0xc2..=0xdf => (2, (lead & 0x1f) as u32),
+            0xe0..=0xef => (3, (lead & 0x0f) as u32),
+            0xf0..=0xf4 => (4, (lead & 0x07) as u32),
+            _ => return Some(char::REPLACEMENT_CHARACTER),
+        };
+
+        // the first continuation byte also rules out overlong forms, surrogates and
+        // code points above U+10FFFF, like String::from_utf8_lossy
+        let first = match lead {
+            0xe0 => 0xa0..=0xbf,
+            0xed => 0x80..=0x9f,
+            0xf0 => 0x90..=0xbf,
+            0xf4 => 0x80..=0x8f,
+            _ => 0x80..=0xbf,
+        };
+
+        for n in 1..width {
+            // an invalid or truncated sequence leaves the offending byte for the next char
+            match self.peek_byte() {
+                Some(b) if (n > 1 || first.contains(&b)) && b & 0xc0 == 0x80 => {
+                    self.reader.consume(1);
+                    code = (code << 6) | (b & 0x3f) as u32;
+                }
+                _ => return Some(char::REPLACEMENT_CHARACTER),
+            }
+        }
+
+        Some(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+}
@@ -0,0 +1,54 @@
+// compares brute force and Aho-Corasick word counts on a generated board
+//
+//     cargo run --release -p d04 --example d04_bench -- [size] [words]
+
+use aoc_common::XorShift;
+use d04::{Board, SearchMode};
+
+const ALPHABET: &[u8] = b"XMASE";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let size = args.next().and_then(|s| s.parse().ok()).unwrap_or(300);
+    let n_words = args.next().and_then(|s| s.parse().ok()).unwrap_or(300);
+
+    let mut rng = XorShift::new(0x2024_0004);
+
+    let mut input = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        for _ in 0..size {
+            input.push(ALPHABET[rng.below(ALPHABET.len())] as char);
+        }
+        input.push('\n');
+    }
+    let board = Board::from_input(&input).unwrap();
+
+    let words = (0..n_words)
+        .map(|_| {
+            let len = 1 + rng.below(7);
+            (0..len)
+                .map(|_| ALPHABET[rng.below(ALPHABET.len())] as char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    let words = words.iter().map(|w| w.as_str()).collect::<Vec<_>>();
+
+    println!("board: {}x{}, words: {}", size, size, words.len());
+
+    let t = std::time::Instant::now();
+    let expected = board.count_words(&words, SearchMode::BruteForce);
+    let dt_brute = t.elapsed();
+
+    let t = std::time::Instant::now();
+    let result = board.count_words(&words, SearchMode::AhoCorasick);
+    let dt_automaton = t.elapsed();
+
+    assert_eq!(expected, result, "search modes disagree");
+
+    println!("BruteForce:  {:?}", dt_brute);
+    println!("AhoCorasick: {:?}", dt_automaton);
+    println!(
+        "speedup:     {:.1}x",
+        dt_brute.as_secs_f64() / dt_automaton.as_secs_f64()
+    );
+}
@@ -0,0 +1,43 @@
+// prints the d04 board with only the matched cells kept
+//
+//     cargo run -p d04 --example render -- <input> [--part 1|2] [--color] [--output <file>]
+
+use d04::{Board, Highlight};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let mut input_path = None;
+    let mut highlight = Highlight::Xmas;
+    let mut color = false;
+    let mut output = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--part" => match iter.next().map(|s| s.as_str()) {
+                Some("1") => highlight = Highlight::Xmas,
+                Some("2") => highlight = Highlight::CrossMas,
+                _ => usage(),
+            },
+            "--color" => color = true,
+            "--output" => output = Some(iter.next().unwrap_or_else(|| usage())),
+            path if input_path.is_none() => input_path = Some(path),
+            _ => usage(),
+        }
+    }
+
+    let input = std::fs::read_to_string(input_path.unwrap_or_else(|| usage())).unwrap();
+    let board = Board::from_input(&input).unwrap();
+    let rendered = board.render(highlight, color);
+
+    match output {
+        Some(path) => std::fs::write(path, rendered).unwrap(),
+        None => print!("{}", rendered),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: render <input> [--part 1|2] [--color] [--output <file>]");
+    std::process::exit(1);
+}
@@ -1,20 +1,62 @@
+mod multi;
+mod pattern;
+mod render;
+
+pub use multi::SearchMode;
+pub use pattern::Pattern;
+pub use render::{explain, Highlight};
+
 pub fn solve(input: &str) -> aoc_common::AocResult {
     let board = Board::from_input(input)?;
-    let count_xmas = board.count_xmas();
+    let count_xmas = board.count_word("XMAS", Overlaps::Allowed);
     let count_mas = board.count_mas();
 
     Ok(format!("d04/01 = {}, d04/02 = {}", count_xmas, count_mas))
 }
 
-#[derive(Debug)]
-struct Board {
+// all 8 directions as (di, dj), i grows downwards and j to the right
+pub const DIRECTIONS: [(i64, i64); 8] = [
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlaps {
+    Allowed,
+    // matches are taken in scan order and may not share a cell with an earlier match
+    Disjoint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordMatch {
+    pub start: (usize, usize),
+    pub direction: (i64, i64),
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatch {
+    // position of the top left corner of the pattern
+    pub origin: (usize, usize),
+    // board cells covered by the non wildcard cells of the pattern
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
     width: usize,
     height: usize,
-    data: Vec<BoardCell>,
+    data: Vec<char>,
 }
 
 impl Board {
-    fn from_input(input: &str) -> Result<Self, aoc_common::AocError> {
+    pub fn from_input(input: &str) -> Result<Self, aoc_common::AocError> {
         let mut width = 0;
         let mut height = 0;
         let mut data = Vec::new();
@@ -28,15 +70,7 @@ impl Board {
             let mut local_width = 0;
             for c in line.chars() {
                 local_width += 1;
-
-                let newval = match c {
-                    'X' => BoardCell::X,
-                    'M' => BoardCell::M,
-                    'A' => BoardCell::A,
-                    'S' => BoardCell::S,
-                    _ => BoardCell::None,
-                };
-                data.push(newval);
+                data.push(c);
             }
 
             if width == 0 {
@@ -57,39 +91,113 @@ impl Board {
         })
     }
 
-    fn count_xmas(&self) -> usize {
-        let mut count = 0;
-        for index in 0..self.data.len() {
-            count += self.count_xmas_by_index(index);
-        }
-        count
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
     }
 
-    fn count_xmas_by_index(&self, index: usize) -> usize {
-        if self.data[index] != BoardCell::X {
-            return 0;
+    pub fn get(&self, i: usize, j: usize) -> Option<char> {
+        if i < self.height && j < self.width {
+            Some(self.data[i * self.width + j])
+        } else {
+            None
         }
+    }
 
-        let xmas = [BoardCell::X, BoardCell::M, BoardCell::A, BoardCell::S];
+    pub fn count_word(&self, word: &str, overlaps: Overlaps) -> usize {
+        self.find_word(word, overlaps).len()
+    }
 
-        let i = (index / self.width) as i64;
-        let j = (index % self.width) as i64;
+    pub fn find_word(&self, word: &str, overlaps: Overlaps) -> Vec<WordMatch> {
+        let word = word.chars().collect::<Vec<_>>();
+        let Some(&first) = word.first() else {
+            return Vec::new();
+        };
 
-        let mut count = 0;
-        for di in -1..=1 {
-            for dj in -1..=1 {
-                if di == 0 && dj == 0 {
+        let mut used = vec![false; self.data.len()];
+        let mut result = Vec::new();
+
+        for index in 0..self.data.len() {
+            if self.data[index] != first {
+                continue;
+            }
+
+            let i = (index / self.width) as i64;
+            let j = (index % self.width) as i64;
+
+            for (di, dj) in DIRECTIONS {
+                // a single letter word reads the same in every direction
+                if word.len() == 1 && (di, dj) != DIRECTIONS[0] {
+                    continue;
+                }
+
+                let indices = self
+                    .ray_indices(i, di, j, dj)
+                    .take(word.len())
+                    .collect::<Vec<_>>();
+
+                if indices.len() != word.len()
+                    || indices
+                        .iter()
+                        .zip(word.iter())
+                        .any(|(&k, &c)| self.data[k] != c)
+                {
                     continue;
                 }
 
-                let ray = self.ray::<4>(i, di, j, dj);
-                if ray == xmas {
-                    count += 1;
+                if overlaps == Overlaps::Disjoint {
+                    if indices.iter().any(|&k| used[k]) {
+                        continue;
+                    }
+                    for &k in indices.iter() {
+                        used[k] = true;
+                    }
                 }
+
+                result.push(WordMatch {
+                    start: (i as usize, j as usize),
+                    direction: (di, dj),
+                    cells: indices.iter().map(|&k| self.coordinates(k)).collect(),
+                });
             }
         }
 
-        count
+        result
+    }
+
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<PatternMatch> {
+        let mut result = Vec::new();
+
+        if pattern.height() > self.height || pattern.width() > self.width {
+            return result;
+        }
+
+        for i in 0..=(self.height - pattern.height()) {
+            for j in 0..=(self.width - pattern.width()) {
+                let matches = pattern
+                    .cells()
+                    .all(|(pi, pj, c)| self.data[(i + pi) * self.width + j + pj] == c);
+
+                if matches {
+                    result.push(PatternMatch {
+                        origin: (i, j),
+                        cells: pattern
+                            .cells()
+                            .map(|(pi, pj, _)| (i + pi, j + pj))
+                            .collect(),
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    fn coordinates(&self, index: usize) -> (usize, usize) {
+        (index / self.width, index % self.width)
     }
 
     fn count_mas(&self) -> usize {
@@ -102,36 +210,32 @@ impl Board {
     }
 
     fn count_mas_by_index(&self, index: usize) -> usize {
-        if self.data[index] != BoardCell::A {
-            return 0;
-        }
+        self.cross_mas_directions(index).count()
+    }
 
+    // directions of the X-MAS crosses centered on index
+    fn cross_mas_directions(&self, index: usize) -> impl Iterator<Item = Direction> + '_ {
         let i = (index / self.width) as i64;
         let j = (index % self.width) as i64;
 
-        let mas = [BoardCell::M, BoardCell::A, BoardCell::S];
-        let mut count = 0;
+        let mas = [Some('M'), Some('A'), Some('S')];
+        let centered = self.data[index] == 'A';
 
-        for direction in [
-            Direction::Right,
-            Direction::Up,
-            Direction::Left,
-            Direction::Down,
-        ] {
-            let rays = self.cross_rays(i, j, direction);
-            if rays[0] == mas && rays[1] == mas {
-                count += 1;
+        Direction::ALL.into_iter().filter(move |&direction| {
+            if !centered {
+                return false;
             }
-        }
 
-        count
+            let rays = self.cross_rays(i, j, direction);
+            rays[0] == mas && rays[1] == mas
+        })
     }
 
-    fn ray<const N: usize>(&self, i: i64, di: i64, j: i64, dj: i64) -> [BoardCell; N] {
-        let mut result = [BoardCell::None; N];
+    fn ray<const N: usize>(&self, i: i64, di: i64, j: i64, dj: i64) -> [Option<char>; N] {
+        let mut result = [None; N];
 
         for (i, index2) in self.ray_indices(i, di, j, dj).enumerate().take(N) {
-            result[i] = self.data[index2];
+            result[i] = Some(self.data[index2]);
         }
 
         result
@@ -145,39 +249,37 @@ impl Board {
             .map(move |(ni, nj)| (ni as usize) * self.width + (nj as usize))
     }
 
-    fn cross_rays(&self, i: i64, j: i64, direction: Direction) -> [[BoardCell; 3]; 2] {
-        let mut result = [[BoardCell::None; 3]; 2];
-
-        let (i1, i2) = match direction {
-            Direction::Right | Direction::Left => (i + 1, i - 1),
-            Direction::Up => (i + 1, i + 1),
-            Direction::Down => (i - 1, i - 1),
-        };
-
-        let (j1, j2) = match direction {
-            Direction::Up | Direction::Down => (j - 1, j + 1),
-            Direction::Left => (j + 1, j + 1),
-            Direction::Right => (j - 1, j - 1),
-        };
-
-        let di1 = i - i1;
-        let dj1 = j - j1;
-        let di2 = i - i2;
-        let dj2 = j - j2;
+    fn cross_rays(&self, i: i64, j: i64, direction: Direction) -> [[Option<char>; 3]; 2] {
+        let [(i1, di1, j1, dj1), (i2, di2, j2, dj2)] = cross_ray_starts(i, j, direction);
+        [
+            self.ray::<3>(i1, di1, j1, dj1),
+            self.ray::<3>(i2, di2, j2, dj2),
+        ]
+    }
 
-        result[0] = self.ray::<3>(i1, di1, j1, dj1);
-        result[1] = self.ray::<3>(i2, di2, j2, dj2);
-        result
+    fn cross_ray_indices(&self, i: i64, j: i64, direction: Direction) -> Vec<usize> {
+        cross_ray_starts(i, j, direction)
+            .into_iter()
+            .flat_map(|(i, di, j, dj)| self.ray_indices(i, di, j, dj).take(3))
+            .collect()
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum BoardCell {
-    None,
-    X,
-    M,
-    A,
-    S,
+// (i, di, j, dj) of the two diagonals through (i, j), both pointing in `direction`
+fn cross_ray_starts(i: i64, j: i64, direction: Direction) -> [(i64, i64, i64, i64); 2] {
+    let (i1, i2) = match direction {
+        Direction::Right | Direction::Left => (i + 1, i - 1),
+        Direction::Up => (i + 1, i + 1),
+        Direction::Down => (i - 1, i - 1),
+    };
+
+    let (j1, j2) = match direction {
+        Direction::Up | Direction::Down => (j - 1, j + 1),
+        Direction::Left => (j + 1, j + 1),
+        Direction::Right => (j - 1, j - 1),
+    };
+
+    [(i1, i - i1, j1, j - j1), (i2, i - i2, j2, j - j2)]
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -187,3 +289,12 @@ enum Direction {
     Left,
     Down,
 }
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Right,
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+    ];
+}
@@ -0,0 +1,104 @@
+use aho_corasick::AhoCorasick;
+
+use crate::{Board, Overlaps};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    // find_word per word, from every cell in every direction
+    BruteForce,
+    // one automaton over every row, column and diagonal, read in both directions
+    AhoCorasick,
+}
+
+impl Board {
+    // occurrences per word in all 8 directions, overlaps allowed
+    pub fn count_words(&self, words: &[&str], mode: SearchMode) -> Vec<usize> {
+        match mode {
+            SearchMode::BruteForce => words
+                .iter()
+                .map(|w| self.count_word(w, Overlaps::Allowed))
+                .collect(),
+            SearchMode::AhoCorasick => self.count_words_aho_corasick(words),
+        }
+    }
+
+    fn count_words_aho_corasick(&self, words: &[&str]) -> Vec<usize> {
+        // the automaton gets each distinct non empty word once
+        let mut unique: Vec<&str> = Vec::new();
+        let word_ids = words
+            .iter()
+            .map(|&w| {
+                if w.is_empty() {
+                    return None;
+                }
+                match unique.iter().position(|&u| u == w) {
+                    Some(p) => Some(p),
+                    None => {
+                        unique.push(w);
+                        Some(unique.len() - 1)
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut counts = vec![0; unique.len()];
+        if !unique.is_empty() {
+            let automaton = AhoCorasick::new(&unique).unwrap();
+            let single_char = unique
+                .iter()
+                .map(|w| w.chars().count() == 1)
+                .collect::<Vec<_>>();
+
+            for (line, counts_single_chars) in self.lines() {
+                let reversed = line.chars().rev().collect::<String>();
+                for (is_reversed, haystack) in [(false, &line), (true, &reversed)] {
+                    for m in automaton.find_overlapping_iter(haystack.as_str()) {
+                        let id = m.pattern().as_usize();
+                        // a single letter is one match, not one per direction
+                        if !single_char[id] || (counts_single_chars && !is_reversed) {
+                            counts[id] += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        word_ids
+            .into_iter()
+            .map(|id| id.map(|id| counts[id]).unwrap_or(0))
+            .collect()
+    }
+
+    // (line, is a row) for every row, column, diagonal and anti diagonal
+    fn lines(&self) -> Vec<(String, bool)> {
+        let width = self.width as i64;
+        let height = self.height as i64;
+
+        let mut starts = Vec::new();
+        for i in 0..height {
+            starts.push((i, 0, 0, 1));
+            starts.push((i, 0, 1, 1));
+            starts.push((i, width - 1, 1, -1));
+        }
+        for j in 0..width {
+            starts.push((0, j, 1, 0));
+            if j > 0 {
+                starts.push((0, j, 1, 1));
+            }
+            if j < width - 1 {
+                starts.push((0, j, 1, -1));
+            }
+        }
+
+        starts
+            .into_iter()
+            .map(|(i, j, di, dj)| {
+                let line = self
+                    .ray_indices(i, di, j, dj)
+                    .map(|k| self.data[k])
+                    .collect::<String>();
+                (line, (di, dj) == (0, 1))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,99 @@
+// rectangular stencil, None cells match anything
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    width: usize,
+    height: usize,
+    data: Vec<Option<char>>,
+}
+
+impl Pattern {
+    pub fn parse(input: &str, wildcard: char) -> Result<Self, aoc_common::AocError> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut data = Vec::new();
+
+        for line in input.lines().map(|l| l.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+
+            height += 1;
+            let mut local_width = 0;
+            for c in line.chars() {
+                local_width += 1;
+                data.push(if c == wildcard { None } else { Some(c) });
+            }
+
+            if width == 0 {
+                width = local_width;
+            } else if width != local_width {
+                return Err(aoc_common::AocError::InvalidInput);
+            }
+        }
+
+        if width == 0 || height == 0 || data.iter().all(|c| c.is_none()) {
+            return Err(aoc_common::AocError::InvalidInput);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    // the four orientations of the X-MAS cross from part 2
+    pub fn x_mas() -> Vec<Self> {
+        Self::parse("M.S\n.A.\nM.S", '.').unwrap().rotations()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // (i, j, char) of every non wildcard cell
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .filter_map(|(index, c)| c.map(|c| (index / self.width, index % self.width, c)))
+    }
+
+    // clockwise by 90 degrees
+    pub fn rotated(&self) -> Self {
+        let width = self.height;
+        let height = self.width;
+        let mut data = vec![None; self.data.len()];
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                data[j * width + (width - 1 - i)] = self.data[i * self.width + j];
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    // distinct rotations, starting with self
+    pub fn rotations(&self) -> Vec<Self> {
+        let mut result = vec![self.clone()];
+        let mut current = self.clone();
+
+        for _ in 0..3 {
+            current = current.rotated();
+            if !result.contains(&current) {
+                result.push(current.clone());
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use crate::{Board, Direction, Overlaps, DIRECTIONS};
+
+const COLORS: [&str; 8] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m", "\x1b[91m", "\x1b[92m",
+];
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    // part 1, XMAS in any of the 8 directions
+    Xmas,
+    // part 2, two MAS crossing in an X
+    CrossMas,
+}
+
+// both parts as in the puzzle illustrations, for the runner's verbose mode
+pub fn explain(input: &str) -> aoc_common::AocResult {
+    let board = Board::from_input(input)?;
+
+    let mut out = String::new();
+    writeln!(out, "XMAS:").unwrap();
+    write!(out, "{}", board.render(Highlight::Xmas, false)).unwrap();
+    writeln!(out, "X-MAS:").unwrap();
+    write!(out, "{}", board.render(Highlight::CrossMas, false)).unwrap();
+    write!(
+        out,
+        "d04/01 = {}, d04/02 = {}",
+        board.count_word("XMAS", Overlaps::Allowed),
+        board.count_mas()
+    )
+    .unwrap();
+
+    Ok(out)
+}
+
+impl Board {
+    // cells outside of every match become '.', with color each match direction gets its own
+    pub fn render(&self, highlight: Highlight, color: bool) -> String {
+        // color index per cell, the first match to claim a cell decides
+        let mut cells: Vec<Option<usize>> = vec![None; self.data.len()];
+
+        match highlight {
+            Highlight::Xmas => {
+                for m in self.find_word("XMAS", Overlaps::Allowed) {
+                    let color_index = DIRECTIONS.iter().position(|&d| d == m.direction).unwrap();
+                    for (i, j) in m.cells {
+                        cells[i * self.width + j].get_or_insert(color_index);
+                    }
+                }
+            }
+
+            Highlight::CrossMas => {
+                for index in 0..self.data.len() {
+                    let i = (index / self.width) as i64;
+                    let j = (index % self.width) as i64;
+
+                    for direction in self.cross_mas_directions(index) {
+                        let color_index =
+                            Direction::ALL.iter().position(|&d| d == direction).unwrap();
+                        for k in self.cross_ray_indices(i, j, direction) {
+                            cells[k].get_or_insert(color_index);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut result = String::with_capacity((self.width + 1) * self.height);
+        for (index, cell) in cells.iter().enumerate() {
+            match cell {
+                Some(c) if color => {
+                    write!(result, "{}{}{}", COLORS[*c], self.data[index], RESET).unwrap()
+                }
+                Some(_) => result.push(self.data[index]),
+                None => result.push('.'),
+            }
+
+            if index % self.width == self.width - 1 {
+                result.push('\n');
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt::Write;
+
+use crate::{parse_input, Input, Rules, Update};
+
+// rule before|after, broken because `after` is printed first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub before: usize,
+    pub after: usize,
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+// positions refer to the update as it is right before the move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub page: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    // position of the update in the input, starting at 0
+    pub index: usize,
+    pub pages: Vec<usize>,
+    pub violations: Vec<Violation>,
+    // as few moves as possible, applied in order they sort the update
+    pub moves: Vec<Move>,
+}
+
+// one diagnosis per update that breaks at least one rule
+pub fn diagnose(input: &str) -> Result<Vec<Diagnosis>, aoc_common::AocError> {
+    let Input { rules, updates, .. } = parse_input(input)?;
+
+    let mut result = Vec::new();
+    for (index, update) in updates.data.iter().enumerate() {
+        if !update.valid(&rules) {
+            result.push(update.diagnose(index, &rules)?);
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn explain(input: &str) -> aoc_common::AocResult {
+    let mut out = String::new();
+
+    for (line, before, after) in parse_input(input)?.unused_rules {
+        writeln!(
+            out,
+            "rule {}|{} on line {} refers to a page that is in no update",
+            before, after, line
+        )
+        .unwrap();
+    }
+
+    for d in diagnose(input)? {
+        let pages = d
+            .pages
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(out, "update {} ({}) is invalid:", d.index + 1, pages).unwrap();
+
+        for v in d.violations.iter() {
+            writeln!(
+                out,
+                "    breaks {}|{}: {} at position {}, {} at position {}",
+                v.before, v.after, v.before, v.before_position, v.after, v.after_position
+            )
+            .unwrap();
+        }
+
+        for m in d.moves.iter() {
+            writeln!(
+                out,
+                "    move {} from position {} to {}",
+                m.page, m.from, m.to
+            )
+            .unwrap();
+        }
+    }
+
+    write!(out, "{}", crate::solve(input)?).unwrap();
+    Ok(out)
+}
+
+impl Update {
+    fn diagnose(&self, index: usize, rules: &Rules) -> Result<Diagnosis, aoc_common::AocError> {
+        let mut violations = Vec::new();
+        for (after_position, &after) in self.data.iter().enumerate() {
+            let later = self.data.iter().enumerate().skip(after_position + 1);
+            for (before_position, &before) in later {
+                if rules.get(&after).is_some_and(|r| r.contains(&before)) {
+                    violations.push(Violation {
+                        before,
+                        after,
+                        before_position,
+                        after_position,
+                    });
+                }
+            }
+        }
+
+        let mut sorted = Update::new(self.data.clone());
+        sorted.sort(rules)?;
+
+        Ok(Diagnosis {
+            index,
+            pages: self.data.clone(),
+            violations,
+            moves: minimal_moves(&self.data, &sorted.data),
+        })
+    }
+}
+
+// pages on a longest subsequence already in target order stay put, every other page moves once
+fn minimal_moves(current: &[usize], target: &[usize]) -> Vec<Move> {
+    let rank = |page: usize| target.iter().position(|&p| p == page).unwrap();
+    let ranks = current.iter().map(|&p| rank(p)).collect::<Vec<_>>();
+
+    let mut placed = vec![false; target.len()];
+    for k in longest_increasing_subsequence(&ranks) {
+        placed[ranks[k]] = true;
+    }
+
+    let mut pages = current.to_vec();
+    let mut moves = Vec::new();
+
+    for (r, &page) in target.iter().enumerate() {
+        if placed[r] {
+            continue;
+        }
+
+        let from = pages.iter().position(|&p| p == page).unwrap();
+        pages.remove(from);
+
+        // right before the first placed page that has to come after it
+        let to = pages
+            .iter()
+            .position(|&p| {
+                let rp = rank(p);
+                placed[rp] && rp > r
+            })
+            .unwrap_or(pages.len());
+        pages.insert(to, page);
+        placed[r] = true;
+
+        moves.push(Move { page, from, to });
+    }
+
+    debug_assert_eq!(pages, target);
+    moves
+}
+
+// indices into values, patience sorting
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // tails[l] is the index of the smallest tail of an increasing run of length l + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut parent = vec![None; values.len()];
+
+    for (k, &v) in values.iter().enumerate() {
+        let l = tails.partition_point(|&t| values[t] < v);
+        if l > 0 {
+            parent[k] = Some(tails[l - 1]);
+        }
+        if l == tails.len() {
+            tails.push(k);
+        } else {
+            tails[l] = k;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut current = tails.last().copied();
+    while let Some(k) = current {
+        result.push(k);
+        current = parent[k];
+    }
+    result.reverse();
+    result
+}
@@ -1,11 +1,19 @@
+mod diagnose;
+mod rule_set;
+
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
 use aoc_common::split_to_array;
 
+pub use diagnose::{diagnose, explain, Diagnosis, Move, Violation};
+pub use rule_set::RuleSet;
+
 type Rules = HashMap<usize, HashSet<usize>>;
 
 pub fn solve(input: &str) -> aoc_common::AocResult {
-    let (rules, mut updates) = parse_input(input)?;
+    let Input {
+        rules, mut updates, ..
+    } = parse_input(input)?;
 
     let mut s1 = 0;
     let mut s2 = 0;
@@ -13,7 +21,7 @@ pub fn solve(input: &str) -> aoc_common::AocResult {
         if u.valid(&rules) {
             s1 += u.center();
         } else {
-            u.sort(&rules);
+            u.sort(&rules)?;
             debug_assert!(u.valid(&rules));
             s2 += u.center();
         }
@@ -22,48 +30,118 @@ pub fn solve(input: &str) -> aoc_common::AocResult {
     Ok(format!("d05/01 = {}, d05/02 = {}", s1, s2))
 }
 
-fn parse_input(input: &str) -> Result<(Rules, Updates), aoc_common::AocError> {
-    let mut rules = Rules::default();
-    let mut update = Updates::new();
-
-    let input = input.trim();
-    let mut line_iter = input.lines().map(|l| l.trim());
+#[derive(Debug)]
+struct Input {
+    rules: Rules,
+    updates: Updates,
+    // (line, before, after) of rules with a page that is part of no update
+    unused_rules: Vec<(usize, usize, usize)>,
+}
 
-    for line in line_iter.by_ref() {
+// line numbers in errors start at 1 and count every line of the input
+fn parse_input(input: &str) -> Result<Input, aoc_common::AocError> {
+    let mut rules = Rules::default();
+    let mut rule_lines = Vec::new();
+    let mut updates = Updates::new();
+
+    let mut line_iter = input
+        .lines()
+        .map(|l| l.trim())
+        .enumerate()
+        .map(|(i, l)| (i + 1, l))
+        .skip_while(|(_, l)| l.is_empty());
+
+    let mut separated = false;
+    for (n, line) in line_iter.by_ref() {
         if line.is_empty() {
+            separated = true;
             break;
         }
 
-        let nums = split_to_array::<2>(line, "|").ok_or(aoc_common::AocError::InvalidInput)?;
-
-        let n1 = nums[0].parse::<usize>()?;
-        let n2 = nums[1].parse::<usize>()?;
+        let [s1, s2] = split_to_array::<2>(line, "|")
+            .filter(|_| line.matches('|').count() == 1)
+            .ok_or_else(|| invalid(n, format!("expected a rule `a|b`, found `{}`", line)))?;
+
+        let n1 = parse_page(n, s1)?;
+        let n2 = parse_page(n, s2)?;
+        if n1 == n2 {
+            return Err(invalid(
+                n,
+                format!("rule {}|{} orders a page before itself", n1, n2),
+            ));
+        }
 
-        rules.entry(n2).or_insert_with(HashSet::default);
         rules.entry(n2).or_default().insert(n1);
+        rule_lines.push((n, n1, n2));
+    }
+
+    if !separated {
+        return Err(aoc_common::AocError::InvalidInputReason(
+            "missing the empty line between rules and updates".into(),
+        ));
     }
 
-    for line in line_iter {
+    let mut seen = HashSet::default();
+    for (n, line) in line_iter {
         if line.is_empty() {
             continue;
         }
 
-        let vals = {
-            let vals: Result<Vec<usize>, _> = line
-                .split(',')
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .map(|s| s.parse())
-                .collect();
-            vals?
-        };
-
-        if !vals.is_empty() {
-            update.push_vec(vals);
+        if line.contains('|') {
+            return Err(invalid(n, format!("rule `{}` within the updates", line)));
+        }
+
+        let mut pages = Vec::new();
+        for (k, s) in line.split(',').enumerate() {
+            let page = parse_page(n, s)
+                .map_err(|_| invalid(n, format!("page {} of the update is `{}`", k, s.trim())))?;
+
+            if let Some(first) = pages.iter().position(|&p| p == page) {
+                return Err(invalid(
+                    n,
+                    format!("page {} appears at positions {} and {}", page, first, k),
+                ));
+            }
+            pages.push(page);
         }
+
+        if pages.len() % 2 == 0 {
+            return Err(invalid(
+                n,
+                format!("update has {} pages and therefore no center", pages.len()),
+            ));
+        }
+
+        seen.extend(pages.iter().copied());
+        updates.push_vec(pages);
+    }
+
+    if updates.data.is_empty() {
+        return Err(aoc_common::AocError::InvalidInputReason(
+            "no updates after the rules".into(),
+        ));
     }
 
-    Ok((rules, update))
+    let unused_rules = rule_lines
+        .into_iter()
+        .filter(|(_, a, b)| !seen.contains(a) || !seen.contains(b))
+        .collect();
+
+    Ok(Input {
+        rules,
+        updates,
+        unused_rules,
+    })
+}
+
+fn parse_page(line: usize, s: &str) -> Result<usize, aoc_common::AocError> {
+    s.trim()
+        .parse()
+        .map_err(|e| invalid(line, format!("`{}` is not a page: {}", s.trim(), e)))
+}
+
+fn invalid(line: usize, reason: String) -> aoc_common::AocError {
+    aoc_common::AocError::InvalidInputReason(format!("line {}: {}", line, reason))
 }
 
 #[derive(Debug)]
@@ -99,28 +177,112 @@ impl Update {
         self.data[self.data.len() / 2]
     }
 
-    fn sort(&mut self, rules: &Rules) {
-        bubble_sort(&mut self.data, |a, b| {
-            let b_before_a = if let Some(r) = rules.get(a) {
-                r.contains(b)
-            } else {
-                false
+    // Kahn's algorithm on the subgraph of the rules induced by the pages of this update
+    fn sort(&mut self, rules: &Rules) -> Result<(), OrderingError> {
+        let n = self.data.len();
+
+        let mut successors = vec![Vec::new(); n];
+        let mut in_degree = vec![0; n];
+        for (k, page) in self.data.iter().enumerate() {
+            let Some(predecessors) = rules.get(page) else {
+                continue;
             };
+            for (l, other) in self.data.iter().enumerate() {
+                if predecessors.contains(other) {
+                    successors[l].push(k);
+                    in_degree[k] += 1;
+                }
+            }
+        }
+
+        let mut sorted = Vec::with_capacity(n);
+        let mut done = vec![false; n];
+
+        while sorted.len() < n {
+            let mut ready = (0..n).filter(|&k| !done[k] && in_degree[k] == 0);
 
-            let a_before_b = if let Some(r) = rules.get(b) {
-                r.contains(a)
-            } else {
-                false
+            let Some(next) = ready.next() else {
+                return Err(OrderingError::Cycle(self.find_cycle(rules, &done)));
             };
 
-            if b_before_a {
-                std::cmp::Ordering::Greater
-            } else if a_before_b {
-                std::cmp::Ordering::Less
-            } else {
-                std::cmp::Ordering::Equal
+            if let Some(other) = ready.next() {
+                let mut pages = vec![self.data[next], self.data[other]];
+                pages.extend(ready.map(|k| self.data[k]));
+                return Err(OrderingError::Ambiguous(pages));
             }
-        });
+
+            done[next] = true;
+            sorted.push(self.data[next]);
+            for &k in successors[next].iter() {
+                in_degree[k] -= 1;
+            }
+        }
+
+        self.data = sorted;
+        Ok(())
+    }
+
+    // every page left over by Kahn's algorithm has a predecessor that is left over as well,
+    // so walking predecessors has to run into a cycle
+    fn find_cycle(&self, rules: &Rules, done: &[bool]) -> Vec<usize> {
+        let remaining = (0..self.data.len())
+            .filter(|&k| !done[k])
+            .map(|k| self.data[k])
+            .collect::<Vec<_>>();
+
+        let mut path = vec![remaining[0]];
+        loop {
+            let current = path[path.len() - 1];
+            let predecessor = remaining
+                .iter()
+                .copied()
+                .find(|p| rules.get(&current).is_some_and(|r| r.contains(p)))
+                .unwrap();
+
+            if let Some(start) = path.iter().position(|&p| p == predecessor) {
+                // path runs backwards along the rules
+                let mut cycle = path[start..].to_vec();
+                cycle.reverse();
+                return cycle;
+            }
+
+            path.push(predecessor);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderingError {
+    // pages that have to come before themselves, in rule order
+    Cycle(Vec<usize>),
+    // pages that are not ordered relative to each other by any rule
+    Ambiguous(Vec<usize>),
+}
+
+impl std::fmt::Display for OrderingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn join(pages: &[usize], sep: &str) -> String {
+            pages
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(sep)
+        }
+
+        match self {
+            OrderingError::Cycle(pages) => {
+                write!(f, "rules form a cycle: {}|{}", join(pages, "|"), pages[0])
+            }
+            OrderingError::Ambiguous(pages) => {
+                write!(f, "no rule orders pages {}", join(pages, ", "))
+            }
+        }
+    }
+}
+
+impl From<OrderingError> for aoc_common::AocError {
+    fn from(e: OrderingError) -> Self {
+        aoc_common::AocError::InvalidInputReason(e.to_string())
     }
 }
 
@@ -142,25 +304,3 @@ impl Updates {
         self.data.iter_mut()
     }
 }
-
-fn bubble_sort<T, F>(values: &mut [T], compare: F)
-where
-    F: Fn(&T, &T) -> std::cmp::Ordering,
-{
-    if values.len() <= 1 {
-        return;
-    }
-
-    for _ in 0..values.len() {
-        let mut swapped = false;
-        for i in 0..(values.len() - 1) {
-            if compare(&values[i], &values[i + 1]) == std::cmp::Ordering::Greater {
-                values.swap(i, i + 1);
-                swapped = true;
-            }
-        }
-        if !swapped {
-            break;
-        }
-    }
-}
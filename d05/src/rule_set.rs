@@ -0,0 +1,251 @@
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::OrderingError;
+
+const BITS: usize = u64::BITS as usize;
+
+// acyclic set of "a before b" rules with a bitset transitive closure, one row per page.
+// pages get dense indices in the order they are first seen, so the size only depends on
+// the number of distinct pages and not on their values.
+// the puzzle input itself is not acyclic as a whole, only per update, which is why
+// solve keeps working on the induced subgraphs instead.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    // page -> index into the rows, and back
+    index: HashMap<usize, usize>,
+    pages: Vec<usize>,
+    // bit b of successors[a] is the rule a|b, by index
+    successors: Vec<Vec<u64>>,
+    // bit b of reach[a]: a has to come before b
+    reach: Vec<Vec<u64>>,
+    words: usize,
+    len: usize,
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Ok(false) if the rule was already known
+    pub fn insert(&mut self, before: usize, after: usize) -> Result<bool, OrderingError> {
+        let (before, after) = (self.intern(before), self.intern(after));
+
+        if get(&self.successors[before], after) {
+            return Ok(false);
+        }
+
+        if before == after || get(&self.reach[after], before) {
+            return Err(OrderingError::Cycle(self.cycle_with(before, after)));
+        }
+
+        set(&mut self.successors[before], after);
+        self.len += 1;
+
+        let mut added = self.reach[after].clone();
+        set(&mut added, after);
+
+        for x in 0..self.reach.len() {
+            if x == before || get(&self.reach[x], before) {
+                for (r, a) in self.reach[x].iter_mut().zip(added.iter()) {
+                    *r |= a;
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    // false if the rule was not part of the set
+    pub fn remove(&mut self, before: usize, after: usize) -> bool {
+        if !self.contains(before, after) {
+            return false;
+        }
+        let (before, after) = (self.index[&before], self.index[&after]);
+
+        clear(&mut self.successors[before], after);
+        self.len -= 1;
+
+        // removing an edge can not be undone locally in the closure
+        self.rebuild_closure();
+        true
+    }
+
+    // direct rule only
+    pub fn contains(&self, before: usize, after: usize) -> bool {
+        match (self.index.get(&before), self.index.get(&after)) {
+            (Some(&before), Some(&after)) => get(&self.successors[before], after),
+            _ => false,
+        }
+    }
+
+    // via any chain of rules
+    pub fn must_precede(&self, a: usize, b: usize) -> bool {
+        match (self.index.get(&a), self.index.get(&b)) {
+            (Some(&a), Some(&b)) => get(&self.reach[a], b),
+            _ => false,
+        }
+    }
+
+    // None if no chain of rules orders a and b
+    pub fn ordering(&self, a: usize, b: usize) -> Option<std::cmp::Ordering> {
+        if a == b {
+            Some(std::cmp::Ordering::Equal)
+        } else if self.must_precede(a, b) {
+            Some(std::cmp::Ordering::Less)
+        } else if self.must_precede(b, a) {
+            Some(std::cmp::Ordering::Greater)
+        } else {
+            None
+        }
+    }
+
+    // sorted by page
+    pub fn rules(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut rules = self
+            .successors
+            .iter()
+            .enumerate()
+            .flat_map(|(a, row)| iter_bits(row).map(move |b| (self.pages[a], self.pages[b])))
+            .collect::<Vec<_>>();
+        rules.sort_unstable();
+        rules.into_iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn intern(&mut self, page: usize) -> usize {
+        if let Some(&index) = self.index.get(&page) {
+            return index;
+        }
+
+        let index = self.pages.len();
+        self.index.insert(page, index);
+        self.pages.push(page);
+        self.reserve(index);
+        index
+    }
+
+    fn reserve(&mut self, index: usize) {
+        if index < self.successors.len() {
+            return;
+        }
+
+        self.words = index / BITS + 1;
+        let pages = self.words * BITS;
+
+        for rows in [&mut self.successors, &mut self.reach] {
+            for row in rows.iter_mut() {
+                row.resize(self.words, 0);
+            }
+            rows.resize(pages, vec![0; self.words]);
+        }
+    }
+
+    // before|after closes a cycle with the existing chain after -> .. -> before, as pages
+    fn cycle_with(&self, before: usize, after: usize) -> Vec<usize> {
+        if before == after {
+            return vec![self.pages[before]];
+        }
+
+        // breadth first search along direct rules, remembering where each page was reached from
+        let mut from = vec![None; self.successors.len()];
+        let mut queue = std::collections::VecDeque::from([after]);
+        from[after] = Some(after);
+
+        while let Some(page) = queue.pop_front() {
+            if page == before {
+                break;
+            }
+            for next in iter_bits(&self.successors[page]) {
+                if from[next].is_none() {
+                    from[next] = Some(page);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut chain = vec![before];
+        let mut page = before;
+        while page != after {
+            page = from[page].unwrap();
+            chain.push(page);
+        }
+        chain.reverse();
+
+        // after|..|before, rotated to start with the new rule before|after
+        let mut result = vec![before];
+        result.extend_from_slice(&chain[..(chain.len() - 1)]);
+        result.iter().map(|&k| self.pages[k]).collect()
+    }
+
+    // every page after all of its successors, then one pass of unions
+    fn rebuild_closure(&mut self) {
+        let n = self.successors.len();
+        let mut visited = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+
+        for start in 0..n {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+
+            let mut stack = vec![(
+                start,
+                iter_bits(&self.successors[start]).collect::<Vec<_>>(),
+            )];
+            while let Some((page, pending)) = stack.last_mut() {
+                match pending.pop() {
+                    Some(next) if !visited[next] => {
+                        visited[next] = true;
+                        let next_pending = iter_bits(&self.successors[next]).collect();
+                        stack.push((next, next_pending));
+                    }
+                    Some(_) => (),
+                    None => {
+                        order.push(*page);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        for &page in order.iter() {
+            let mut row = vec![0; self.words];
+            for next in iter_bits(&self.successors[page]) {
+                set(&mut row, next);
+                for (r, a) in row.iter_mut().zip(self.reach[next].iter()) {
+                    *r |= a;
+                }
+            }
+            self.reach[page] = row;
+        }
+    }
+}
+
+fn get(row: &[u64], k: usize) -> bool {
+    (row[k / BITS] >> (k % BITS)) & 1 == 1
+}
+
+fn set(row: &mut [u64], k: usize) {
+    row[k / BITS] |= 1 << (k % BITS);
+}
+
+fn clear(row: &mut [u64], k: usize) {
+    row[k / BITS] &= !(1 << (k % BITS));
+}
+
+fn iter_bits(row: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    row.iter().enumerate().flat_map(|(w, &word)| {
+        (0..BITS)
+            .filter(move |bit| (word >> bit) & 1 == 1)
+            .map(move |bit| w * BITS + bit)
+    })
+}
@@ -0,0 +1,336 @@
+use std::fmt::Write;
+
+use rayon::prelude::*;
+use rustc_hash::FxHashMap as HashMap;
+
+use crate::trace::{walk, Walk};
+use crate::{parse_guards, Tile, Topology};
+
+// all guards take one step per tick at the same time. guards do not block each other, they only
+// turn at obstacles. two guards collide when they end a tick on the same cell or swap cells
+// during it, and both stop. a guard that repeats a (position, direction) state without ever
+// colliding loops, the others keep walking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fate {
+    // stepped off the map at this tick
+    Exited {
+        tick: usize,
+    },
+    // position is the last cell the guard stood on
+    Collided {
+        tick: usize,
+        position: (usize, usize),
+    },
+    // the states from entry_tick on repeat every period ticks
+    Looping {
+        entry_tick: usize,
+        period: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardReport {
+    pub start: (usize, usize),
+    pub facing: char,
+    pub fate: Fate,
+    // part 1, distinct cells the guard stands on
+    pub visited: usize,
+    // part 2, single obstructions after which this guard loops
+    pub obstructions: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockstep {
+    // in reading order of the starts
+    pub guards: Vec<GuardReport>,
+    // distinct cells any guard stands on
+    pub visited: usize,
+    // single obstructions after which at least one guard loops
+    pub obstructions: usize,
+}
+
+pub fn lockstep(input: &str) -> Result<Lockstep, aoc_common::AocError> {
+    let (guards, field) = parse_guards(input, Topology::Bounded)?;
+
+    let walks = guards.iter().map(|&g| walk(g, &field)).collect::<Vec<_>>();
+    let fates = resolve(&walks.iter().collect::<Vec<_>>());
+
+    let mut on_any_path = vec![false; field.data.len()];
+    let mut visited = Vec::with_capacity(guards.len());
+    for (w, fate) in walks.iter().zip(fates.iter()) {
+        let path = path(w, fate);
+        for &(i, j) in path.iter() {
+            on_any_path[i * field.width + j] = true;
+        }
+        visited.push(path.len());
+    }
+
+    // an obstruction off every path is never reached and changes nothing
+    let candidates = on_any_path
+        .iter()
+        .enumerate()
+        .filter(|(_, &on)| on)
+        .map(|(index, _)| (index / field.width, index % field.width))
+        .filter(|&ij| guards.iter().all(|g| g.position != ij))
+        .collect::<Vec<_>>();
+
+    // per candidate, which guards end up looping
+    let trapped = candidates
+        .par_iter()
+        .map(|&(i, j)| {
+            let mut field = field.clone();
+            field.data[i * field.width + j] |= Tile::Blocked as u8;
+
+            // only guards that would walk into the obstruction change their way
+            let rewalked = guards
+                .iter()
+                .zip(walks.iter())
+                .map(|(&g, w)| {
+                    w.states
+                        .iter()
+                        .any(|p| p.position == (i, j))
+                        .then(|| walk(g, &field))
+                })
+                .collect::<Vec<_>>();
+            let walks = rewalked
+                .iter()
+                .zip(walks.iter())
+                .map(|(r, w)| r.as_ref().unwrap_or(w))
+                .collect::<Vec<_>>();
+
+            resolve(&walks)
+                .iter()
+                .map(|f| matches!(f, Fate::Looping { .. }))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let reports = guards
+        .iter()
+        .enumerate()
+        .map(|(k, g)| GuardReport {
+            start: g.position,
+            facing: g.direction.glyph(),
+            fate: fates[k],
+            visited: visited[k],
+            obstructions: trapped.iter().filter(|t| t[k]).count(),
+        })
+        .collect();
+
+    Ok(Lockstep {
+        guards: reports,
+        visited: on_any_path.iter().filter(|&&on| on).count(),
+        obstructions: trapped.iter().filter(|t| t.contains(&true)).count(),
+    })
+}
+
+// both parts per guard, then for all guards together
+pub fn solve_lockstep(input: &str) -> aoc_common::AocResult {
+    let result = lockstep(input)?;
+
+    let mut out = String::new();
+    for (k, g) in result.guards.iter().enumerate() {
+        let fate = match g.fate {
+            Fate::Exited { tick } => format!("exits at tick {}", tick),
+            Fate::Collided { tick, position } => {
+                format!("collides at tick {} on {:?}", tick, position)
+            }
+            Fate::Looping { entry_tick, period } => {
+                format!("loops from tick {} every {} ticks", entry_tick, period)
+            }
+        };
+        writeln!(
+            out,
+            "guard {} at {:?} facing {}: d06/01 = {}, d06/02 = {}, {}",
+            k + 1,
+            g.start,
+            g.facing,
+            g.visited,
+            g.obstructions,
+            fate
+        )
+        .unwrap();
+    }
+    write!(
+        out,
+        "all guards: d06/01 = {}, d06/02 = {}",
+        result.visited, result.obstructions
+    )
+    .unwrap();
+
+    Ok(out)
+}
+
+// guards only meet in pairs, a three way collision is three pairs on the same tick
+fn resolve(walks: &[&Walk]) -> Vec<Fate> {
+    let mut meetings = Vec::new();
+    for a in 0..walks.len() {
+        for b in (a + 1)..walks.len() {
+            if let Some((tick, swap)) = first_meeting(walks[a], walks[b]) {
+                meetings.push((tick, a, b, swap));
+            }
+        }
+    }
+    meetings.sort();
+
+    // the tick and the position each guard stops at
+    let mut collided: Vec<Option<(usize, (usize, usize))>> = vec![None; walks.len()];
+    for (tick, a, b, swap) in meetings {
+        let free = |c: &Option<(usize, (usize, usize))>| c.is_none_or(|(t, _)| t == tick);
+        if !free(&collided[a]) || !free(&collided[b]) {
+            continue;
+        }
+
+        let stand = if swap { tick - 1 } else { tick };
+        for g in [a, b] {
+            collided[g] = Some((tick, position_at(walks[g], stand).unwrap()));
+        }
+    }
+
+    walks
+        .iter()
+        .zip(collided)
+        .map(|(w, c)| match (c, w.cycle_start) {
+            (Some((tick, position)), _) => Fate::Collided { tick, position },
+            (None, Some(s)) => Fate::Looping {
+                entry_tick: s,
+                period: w.states.len() - 1 - s,
+            },
+            (None, None) => Fate::Exited {
+                tick: w.states.len(),
+            },
+        })
+        .collect()
+}
+
+// distinct cells up to the end of the walk, sorted
+fn path(w: &Walk, fate: &Fate) -> Vec<(usize, usize)> {
+    let last = match *fate {
+        Fate::Collided { tick, position } if position_at(w, tick) == Some(position) => tick,
+        Fate::Collided { tick, .. } => tick - 1,
+        _ => w.states.len() - 1,
+    };
+
+    let mut result = w
+        .states
+        .iter()
+        .take(last + 1)
+        .map(|p| p.position)
+        .collect::<Vec<_>>();
+    result.sort();
+    result.dedup();
+    result
+}
+
+// None once the guard has left the map
+fn position_at(w: &Walk, tick: usize) -> Option<(usize, usize)> {
+    match w.cycle_start {
+        Some(s) if tick >= s => {
+            let period = w.states.len() - 1 - s;
+            Some(w.states[s + (tick - s) % period].position)
+        }
+        _ => w.states.get(tick).map(|p| p.position),
+    }
+}
+
+// first tick on which the two guards share a cell (false) or swap cells (true)
+fn first_meeting(a: &Walk, b: &Walk) -> Option<(usize, bool)> {
+    let (la, lb) = (a.states.len(), b.states.len());
+
+    // up to here at least one of the guards is not yet in its loop
+    let horizon = match (a.cycle_start, b.cycle_start) {
+        (Some(_), Some(_)) => la.max(lb),
+        (Some(_), None) => lb,
+        (None, Some(_)) => la,
+        (None, None) => la.min(lb),
+    };
+
+    for tick in 1..horizon {
+        let (pa, pb) = (position_at(a, tick)?, position_at(b, tick)?);
+        if pa == pb {
+            return Some((tick, false));
+        }
+        if Some(pa) == position_at(b, tick - 1) && Some(pb) == position_at(a, tick - 1) {
+            return Some((tick, true));
+        }
+    }
+
+    match (a.cycle_start, b.cycle_start) {
+        (Some(sa), Some(sb)) => periodic_meeting(a, sa, b, sb, horizon),
+        _ => None,
+    }
+}
+
+// both guards loop from here on, every meeting repeats with the lcm of the periods,
+// so the first one follows from the phases at which both are on the same cell
+fn periodic_meeting(
+    a: &Walk,
+    sa: usize,
+    b: &Walk,
+    sb: usize,
+    from: usize,
+) -> Option<(usize, bool)> {
+    let cycle_a = &a.states[sa..(a.states.len() - 1)];
+    let cycle_b = &b.states[sb..(b.states.len() - 1)];
+    let (pa, pb) = (cycle_a.len(), cycle_b.len());
+
+    let mut phases: HashMap<(usize, usize), Vec<usize>> = HashMap::default();
+    for (y, p) in cycle_b.iter().enumerate() {
+        phases.entry(p.position).or_default().push(y);
+    }
+
+    let mut result: Option<(usize, bool)> = None;
+    let mut keep = |m: (usize, bool)| result = Some(result.map_or(m, |r| r.min(m)));
+
+    for (x, p) in cycle_a.iter().enumerate() {
+        let here = p.position;
+        let next = cycle_a[(x + 1) % pa].position;
+
+        for &y in phases.get(&here).into_iter().flatten() {
+            if let Some(tick) = first_common_tick(sa + x, pa, sb + y, pb, from) {
+                keep((tick, false));
+            }
+        }
+
+        // a goes here -> next while b goes next -> here, phases x and y are the tick before
+        if next == here {
+            continue;
+        }
+        for &y in phases.get(&next).into_iter().flatten() {
+            if cycle_b[(y + 1) % pb].position == here {
+                if let Some(tick) = first_common_tick(sa + x, pa, sb + y, pb, from - 1) {
+                    keep((tick + 1, true));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// smallest tick >= from with tick = ra mod ma and tick = rb mod mb
+fn first_common_tick(ra: usize, ma: usize, rb: usize, mb: usize, from: usize) -> Option<usize> {
+    let (ra, ma, rb, mb, from) = (ra as i128, ma as i128, rb as i128, mb as i128, from as i128);
+
+    let (g, inverse, _) = extended_gcd(ma, mb);
+    if (rb - ra) % g != 0 {
+        return None;
+    }
+
+    // ra + ma * k = rb (mod mb)
+    let k = ((rb - ra) / g * inverse).rem_euclid(mb / g);
+    let tick = ra + ma * k;
+    let lcm = ma / g * mb;
+
+    Some((from + (tick - from).rem_euclid(lcm)) as usize)
+}
+
+// (g, x, y) with a * x + b * y = g
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
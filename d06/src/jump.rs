@@ -0,0 +1,171 @@
+use crate::{Direction, Field, Player};
+
+// obstacles as sorted lists per row and column, so the guard can jump from turn to turn.
+// does not wrap, a torus is walked step by step.
+#[derive(Debug, Clone)]
+pub(crate) struct JumpTable {
+    // obstacle columns of each row
+    rows: Vec<Vec<usize>>,
+    // obstacle rows of each column
+    columns: Vec<Vec<usize>>,
+    // the same for void cells, which end the walk
+    void_rows: Vec<Vec<usize>>,
+    void_columns: Vec<Vec<usize>>,
+}
+
+impl JumpTable {
+    pub(crate) fn new(field: &Field) -> Self {
+        let mut rows = vec![Vec::new(); field.height];
+        let mut columns = vec![Vec::new(); field.width];
+        let mut void_rows = vec![Vec::new(); field.height];
+        let mut void_columns = vec![Vec::new(); field.width];
+
+        for index in 0..field.data.len() {
+            let i = index / field.width;
+            let j = index % field.width;
+            if field.position_blocked((i, j)) {
+                rows[i].push(j);
+                columns[j].push(i);
+            } else if field.position_void((i, j)) {
+                void_rows[i].push(j);
+                void_columns[j].push(i);
+            }
+        }
+
+        Self {
+            rows,
+            columns,
+            void_rows,
+            void_columns,
+        }
+    }
+
+    pub(crate) fn add_obstacle(&mut self, ij: (usize, usize)) {
+        let (i, j) = ij;
+        insert_sorted(&mut self.rows[i], j);
+        insert_sorted(&mut self.columns[j], i);
+    }
+
+    pub(crate) fn remove_obstacle(&mut self, ij: (usize, usize)) {
+        let (i, j) = ij;
+        remove_sorted(&mut self.rows[i], j);
+        remove_sorted(&mut self.columns[j], i);
+    }
+
+    // the cell right in front of the next obstacle, None if the guard walks off the map
+    fn next_stop(&self, player: &Player) -> Option<(usize, usize)> {
+        let (i, j) = player.position;
+
+        match player.direction {
+            Direction::Right => {
+                let c = next_after(&self.rows[i], j)?;
+                match next_after(&self.void_rows[i], j) {
+                    Some(v) if v < c => None,
+                    _ => Some((i, c - 1)),
+                }
+            }
+            Direction::Left => {
+                let c = next_before(&self.rows[i], j)?;
+                match next_before(&self.void_rows[i], j) {
+                    Some(v) if v > c => None,
+                    _ => Some((i, c + 1)),
+                }
+            }
+            Direction::Down => {
+                let r = next_after(&self.columns[j], i)?;
+                match next_after(&self.void_columns[j], i) {
+                    Some(v) if v < r => None,
+                    _ => Some((r - 1, j)),
+                }
+            }
+            Direction::Up => {
+                let r = next_before(&self.columns[j], i)?;
+                match next_before(&self.void_columns[j], i) {
+                    Some(v) if v > r => None,
+                    _ => Some((r + 1, j)),
+                }
+            }
+        }
+    }
+
+    // true -> loop, false -> outside
+    pub(crate) fn sweep(&self, player_start: Player, turns: &mut TurnSet) -> bool {
+        turns.clear();
+
+        let mut player = player_start;
+        loop {
+            let Some(position) = self.next_stop(&player) else {
+                return false;
+            };
+
+            player.position = position;
+            player.direction = player.direction.turned_right();
+
+            if !turns.insert(&player) {
+                return true;
+            }
+        }
+    }
+}
+
+// smallest value above x
+fn next_after(values: &[usize], x: usize) -> Option<usize> {
+    values.get(values.partition_point(|&v| v <= x)).copied()
+}
+
+// largest value below x
+fn next_before(values: &[usize], x: usize) -> Option<usize> {
+    match values.partition_point(|&v| v < x) {
+        0 => None,
+        k => Some(values[k - 1]),
+    }
+}
+
+fn insert_sorted(values: &mut Vec<usize>, value: usize) {
+    if let Err(k) = values.binary_search(&value) {
+        values.insert(k, value);
+    }
+}
+
+fn remove_sorted(values: &mut Vec<usize>, value: usize) {
+    if let Ok(k) = values.binary_search(&value) {
+        values.remove(k);
+    }
+}
+
+// (position, direction) states, e.g. after a turn, cleared in O(1) by bumping the generation
+#[derive(Debug, Clone)]
+pub(crate) struct TurnSet {
+    stamps: Vec<u32>,
+    width: usize,
+    generation: u32,
+}
+
+impl TurnSet {
+    pub(crate) fn new(field: &Field) -> Self {
+        Self {
+            stamps: vec![0; field.width * field.height * 4],
+            width: field.width,
+            generation: 0,
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation == 0 {
+            self.stamps.fill(0);
+            self.generation = 1;
+        }
+    }
+
+    // false if the state was already part of the set
+    pub(crate) fn insert(&mut self, p: &Player) -> bool {
+        let index = (p.position.0 * self.width + p.position.1) * 4 + p.direction.index();
+        if self.stamps[index] == self.generation {
+            return false;
+        }
+
+        self.stamps[index] = self.generation;
+        true
+    }
+}
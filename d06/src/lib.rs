@@ -1,53 +1,177 @@
+mod guards;
+mod jump;
+mod trace;
+
 use rayon::prelude::*;
 
+use jump::{JumpTable, TurnSet};
+
+pub use guards::{lockstep, solve_lockstep, Fate, GuardReport, Lockstep};
+pub use trace::{obstructions, render, LoopTrace, Obstruction};
+
 pub fn solve(input: &str) -> aoc_common::AocResult {
-    let (player, mut field) = parse_input(input)?;
-    let start_position = player.position;
+    solve_with(input, Topology::Bounded)
+}
+
+pub fn solve_with(input: &str, topology: Topology) -> aoc_common::AocResult {
+    let (guards, mut field) = parse_guards(input, topology)?;
+    let [player] = guards[..] else {
+        if topology != Topology::Bounded {
+            return Err(aoc_common::AocError::InvalidInputReason(
+                "several guards are only supported on bounded maps".to_string(),
+            ));
+        }
+        return solve_lockstep(input);
+    };
 
-    let field2 = field.clone();
-    if sweep(player, &mut field) {
+    let jumps = JumpTable::new(&field);
+    let looped = sweep(player, &mut field);
+    let s1 = field.count_visited();
+
+    let s2 = if topology == Topology::Torus {
+        stranding_obstructions(player, &field).len()
+    } else if looped {
         // loop in field
         return Err(aoc_common::AocError::InvalidInput);
-    }
-    let s1 = field.count_visited();
+    } else {
+        loop_obstructions(player, &field, &jumps).len()
+    };
 
-    let visited_fields = field.iter_visited().collect::<Vec<_>>();
+    Ok(format!("d06/01 = {}, d06/02 = {}", s1, s2))
+}
+
+// how the map continues past its edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    // the puzzle, walking off the map ends the walk
+    #[default]
+    Bounded,
+    // leaving on one side enters on the opposite one, so every walk ends in a loop
+    Torus,
+    // rows may differ in length, ' ' and the cells missing from short rows are void.
+    // stepping into the void ends the walk like walking off the map.
+    Void,
+}
+
+// unordered positions of all single obstructions that make the guard loop
+fn loop_obstructions(player: Player, field: &Field, jumps: &JumpTable) -> Vec<(usize, usize)> {
+    let entries = first_entries(player, field);
 
     let n_threads = 16;
 
-    let s2: usize = (0..n_threads)
+    (0..n_threads)
         .par_bridge()
         .map(|thread_index| {
-            let mut field2 = field2.clone();
-            let mut s2 = 0;
-            for index in visited_fields.iter().skip(thread_index).step_by(n_threads) {
-                let i = index / field.width;
-                let j = index % field.width;
-                if (i, j) == start_position {
-                    continue;
+            let mut jumps = jumps.clone();
+            let mut turns = TurnSet::new(field);
+            let mut result = Vec::new();
+            for (candidate, before) in entries.iter().skip(thread_index).step_by(n_threads) {
+                jumps.add_obstacle(*candidate);
+                if jumps.sweep(*before, &mut turns) {
+                    result.push(*candidate);
                 }
-                field2.data[i * field2.width + j] = Tile::Blocked as u8;
-                field2.bitset_reset();
-                if sweep(player, &mut field2) {
-                    s2 += 1;
+                jumps.remove_obstacle(*candidate);
+            }
+            result
+        })
+        .flatten()
+        .collect()
+}
+
+// on a torus the guard can not leave, part 2 counts the single obstructions after which it
+// never gets back to its start instead
+fn stranding_obstructions(player: Player, field: &Field) -> Vec<(usize, usize)> {
+    let entries = first_entries(player, field);
+
+    let n_threads = 16;
+
+    (0..n_threads)
+        .par_bridge()
+        .map(|thread_index| {
+            let mut field = field.clone();
+            let mut states = TurnSet::new(&field);
+            let mut result = Vec::new();
+            for (candidate, before) in entries.iter().skip(thread_index).step_by(n_threads) {
+                let index = candidate.0 * field.width + candidate.1;
+                field.data[index] |= Tile::Blocked as u8;
+                if !returns_to(player, *before, &field, &mut states) {
+                    result.push(*candidate);
                 }
-                field2.data[i * field2.width + j] = 0;
+                field.data[index] &= !(Tile::Blocked as u8);
             }
-            s2
+            result
         })
-        .sum();
+        .flatten()
+        .collect()
+}
 
-    Ok(format!("d06/01 = {}, d06/02 = {}", s1, s2))
+// every state of the loop shows up before the first repeated one, so the start is part of
+// the loop exactly if it comes up before a repeat
+fn returns_to(start: Player, from: Player, field: &Field, states: &mut TurnSet) -> bool {
+    states.clear();
+
+    let mut player = from;
+    loop {
+        let previous = player.position;
+        if !step(&mut player, field) {
+            return false;
+        }
+
+        // boxed in, the guard faces every direction in turn
+        if player.position == previous {
+            return player.position == start.position;
+        }
+
+        if player.position == start.position && player.direction == start.direction {
+            return true;
+        }
+
+        if !states.insert(&player) {
+            return false;
+        }
+    }
+}
+
+// every cell of the path except the start, with the guard right before it first steps onto it.
+// an obstruction there leaves the path up to that point unchanged, so the search can resume
+// from this state instead of the start.
+fn first_entries(player_start: Player, field: &Field) -> Vec<((usize, usize), Player)> {
+    let mut seen = vec![false; field.data.len()];
+    // on a torus the walk ends in a loop instead of outside
+    let mut states = TurnSet::new(field);
+    let mut result = Vec::new();
+
+    let mut player = player_start;
+    seen[player.position.0 * field.width + player.position.1] = true;
+    states.clear();
+
+    loop {
+        let previous = player.position;
+        if !step(&mut player, field) || player.position == previous || !states.insert(&player) {
+            return result;
+        }
+
+        let index = player.position.0 * field.width + player.position.1;
+        if !seen[index] {
+            seen[index] = true;
+            let before = Player {
+                position: previous,
+                direction: player.direction,
+            };
+            result.push((player.position, before));
+        }
+    }
 }
 
-// 0 1 2 3       4     5  6    7
-// x x x blocked right up left down
+// 0 1 2    3       4     5  6    7
+// x x void blocked right up left down
 
 #[derive(Debug, Clone)]
 struct Field {
     data: Vec<u8>,
     width: usize,
     height: usize,
+    topology: Topology,
 }
 
 impl Field {
@@ -57,6 +181,35 @@ impl Field {
         (self.data[index] & (Tile::Blocked as u8)) > 0
     }
 
+    fn position_void(&self, ij: (usize, usize)) -> bool {
+        let (i, j) = ij;
+        let index = i * self.width + j;
+        (self.data[index] & (Tile::Void as u8)) > 0
+    }
+
+    // the next cell in this direction, None past the edge of a map that does not wrap
+    fn neighbour(&self, ij: (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        let (i, j) = ij;
+        let (height, width) = (self.height, self.width);
+
+        if self.topology == Topology::Torus {
+            return Some(match direction {
+                Direction::Right => (i, (j + 1) % width),
+                Direction::Up => ((i + height - 1) % height, j),
+                Direction::Left => (i, (j + width - 1) % width),
+                Direction::Down => ((i + 1) % height, j),
+            });
+        }
+
+        match direction {
+            Direction::Right if j + 1 < width => Some((i, j + 1)),
+            Direction::Up if i > 0 => Some((i - 1, j)),
+            Direction::Left if j > 0 => Some((i, j - 1)),
+            Direction::Down if i + 1 < height => Some((i + 1, j)),
+            _ => None,
+        }
+    }
+
     fn bitset_add(&mut self, p: &Player) {
         let index = p.position.0 * self.width + p.position.1;
         self.data[index] |= p.direction as u8;
@@ -73,12 +226,6 @@ impl Field {
     //     self.data[index] & ALL_DIRECTIONS > 0
     // }
 
-    fn bitset_reset(&mut self) {
-        for d in self.data.iter_mut() {
-            *d &= !ALL_DIRECTIONS;
-        }
-    }
-
     fn count_visited(&self) -> usize {
         self.iter_visited().count()
     }
@@ -97,6 +244,7 @@ impl Field {
 enum Tile {
     Free = 0,
     Blocked = 1 << 4,
+    Void = 1 << 5,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -109,6 +257,30 @@ enum Direction {
 }
 
 impl Direction {
+    // 0..4, for tables indexed by direction
+    fn index(self) -> usize {
+        (self as u8).trailing_zeros() as usize
+    }
+
+    fn from_glyph(c: char) -> Option<Self> {
+        match c {
+            '>' => Some(Self::Right),
+            '^' => Some(Self::Up),
+            '<' => Some(Self::Left),
+            'v' => Some(Self::Down),
+            _ => None,
+        }
+    }
+
+    fn glyph(self) -> char {
+        match self {
+            Self::Right => '>',
+            Self::Up => '^',
+            Self::Left => '<',
+            Self::Down => 'v',
+        }
+    }
+
     fn turned_right(self) -> Self {
         match self {
             Self::Right => Self::Down,
@@ -135,21 +307,46 @@ struct Player {
 }
 
 fn parse_input(input: &str) -> Result<(Player, Field), aoc_common::AocError> {
-    let input = input.trim();
+    let (guards, field) = parse_guards(input, Topology::Bounded)?;
+    match guards.as_slice() {
+        [player] => Ok((*player, field)),
+        _ => Err(aoc_common::AocError::InvalidInputReason(format!(
+            "expected exactly one guard, found {}",
+            guards.len()
+        ))),
+    }
+}
 
-    let mut width = 0;
-    let mut height = 0;
-    let mut player = None;
+// every guard in reading order
+fn parse_guards(
+    input: &str,
+    topology: Topology,
+) -> Result<(Vec<Player>, Field), aoc_common::AocError> {
+    // leading spaces are void cells, so only a void map keeps them
+    let lines = match topology {
+        Topology::Void => input
+            .lines()
+            .map(|l| l.trim_end())
+            .skip_while(|l| l.is_empty())
+            .collect::<Vec<_>>(),
+        _ => input
+            .trim()
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>(),
+    };
+    let lines = match lines.iter().rposition(|l| !l.is_empty()) {
+        Some(last) => &lines[..=last],
+        None => &lines[..0],
+    };
+
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let height = lines.len();
+    let mut guards = Vec::new();
     let mut data = Vec::new();
 
-    for (i, line) in input
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .enumerate()
-    {
-        height += 1;
-
+    for (i, line) in lines.iter().enumerate() {
         let mut local_width = 0;
         for (j, c) in line.chars().enumerate() {
             local_width += 1;
@@ -157,60 +354,53 @@ fn parse_input(input: &str) -> Result<(Player, Field), aoc_common::AocError> {
             match c {
                 '#' => data.push(Tile::Blocked as u8),
                 '.' => data.push(Tile::Free as u8),
-                '^' => {
-                    player = Some(Player {
+                ' ' if topology == Topology::Void => data.push(Tile::Void as u8),
+                c => {
+                    let direction =
+                        Direction::from_glyph(c).ok_or(aoc_common::AocError::InvalidInput)?;
+                    guards.push(Player {
                         position: (i, j),
-                        direction: Direction::Up,
+                        direction,
                     });
                     data.push(Tile::Free as u8);
                 }
-                _ => return Err(aoc_common::AocError::InvalidInput),
             }
         }
 
-        if width != 0 && width != local_width {
-            return Err(aoc_common::AocError::InvalidInput);
+        if local_width != width {
+            if topology != Topology::Void {
+                return Err(aoc_common::AocError::InvalidInput);
+            }
+            data.extend(std::iter::repeat_n(Tile::Void as u8, width - local_width));
         }
-        width = local_width;
     }
 
-    if width == 0 || height == 0 || player.is_none() || data.len() != width * height {
+    if width == 0 || height == 0 || guards.is_empty() || data.len() != width * height {
         return Err(aoc_common::AocError::InvalidInput);
     }
 
     Ok((
-        player.unwrap(),
+        guards,
         Field {
             data,
             width,
             height,
+            topology,
         },
     ))
 }
 
 // true -> still inside, false -> outside
-fn step(player: &mut Player, field: &mut Field) -> bool {
-    let max_j = field.width - 1;
-    let max_i = field.height - 1;
-
+fn step(player: &mut Player, field: &Field) -> bool {
     for _ in 0..4 {
-        let mut invalid_move = false;
-        invalid_move |= (player.direction == Direction::Left) && (player.position.1 == 0);
-        invalid_move |= (player.direction == Direction::Right) && (player.position.1 >= max_j);
-        invalid_move |= (player.direction == Direction::Up) && (player.position.0 == 0);
-        invalid_move |= (player.direction == Direction::Down) && (player.position.0 >= max_i);
-        if invalid_move {
+        let Some(candidate) = field.neighbour(player.position, player.direction) else {
             return false;
-        }
-
-        let (i, j) = player.position;
-        let candidate = match player.direction {
-            Direction::Right => (i, j + 1),
-            Direction::Up => (i - 1, j),
-            Direction::Left => (i, j - 1),
-            Direction::Down => (i + 1, j),
         };
 
+        if field.position_void(candidate) {
+            return false;
+        }
+
         if field.position_blocked(candidate) {
             player.direction = player.direction.turned_right();
         } else {
@@ -219,7 +409,8 @@ fn step(player: &mut Player, field: &mut Field) -> bool {
         }
     }
 
-    false
+    // boxed in on all four sides, the guard spins in place forever
+    true
 }
 
 // true -> loop, false -> outside
@@ -0,0 +1,163 @@
+use crate::{parse_input, step, sweep, Direction, Field, JumpTable, Player, Tile};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopTrace {
+    // first position of the walk that is part of the cycle
+    pub entry: (usize, usize),
+    // steps for one round
+    pub length: usize,
+    // distinct cells of the cycle, in walking order from the entry
+    pub cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Obstruction {
+    pub position: (usize, usize),
+    pub cycle: LoopTrace,
+}
+
+// every obstruction that traps the guard, sorted by position
+pub fn obstructions(input: &str) -> Result<Vec<Obstruction>, aoc_common::AocError> {
+    let (player, mut field) = parse_input(input)?;
+
+    let jumps = JumpTable::new(&field);
+    let clean = field.clone();
+    if sweep(player, &mut field) {
+        return Err(aoc_common::AocError::InvalidInput);
+    }
+
+    let mut positions = crate::loop_obstructions(player, &field, &jumps);
+    positions.sort();
+
+    Ok(positions
+        .into_iter()
+        .map(|position| {
+            let walk = walk(player, &with_obstruction(&clean, position));
+            Obstruction {
+                position,
+                cycle: walk.cycle.expect("jump table and walk disagree on a loop"),
+            }
+        })
+        .collect())
+}
+
+// the map as in the puzzle illustrations: the guard's path drawn with |, - and +,
+// the obstruction as O
+pub fn render(input: &str, obstruction: (usize, usize)) -> Result<String, aoc_common::AocError> {
+    let (player, field) = parse_input(input)?;
+
+    let (i, j) = obstruction;
+    if i >= field.height
+        || j >= field.width
+        || field.position_blocked(obstruction)
+        || obstruction == player.position
+    {
+        return Err(aoc_common::AocError::InvalidInput);
+    }
+
+    let field = with_obstruction(&field, obstruction);
+    let walk = walk(player, &field);
+
+    // directions of movement per cell, in the Field bitset encoding
+    let mut moves = vec![0u8; field.data.len()];
+    for w in walk.states.windows(2) {
+        let (from, to) = (w[0], w[1]);
+        // leaving a cell and entering the next one, which covers turns on the spot
+        moves[from.position.0 * field.width + from.position.1] |= to.direction as u8;
+        moves[to.position.0 * field.width + to.position.1] |= to.direction as u8;
+    }
+
+    let vertical = Direction::Up as u8 | Direction::Down as u8;
+    let horizontal = Direction::Left as u8 | Direction::Right as u8;
+
+    let mut result = String::with_capacity((field.width + 1) * field.height);
+    for (index, &moved) in moves.iter().enumerate() {
+        let ij = (index / field.width, index % field.width);
+
+        let c = if ij == obstruction {
+            'O'
+        } else if field.position_blocked(ij) {
+            '#'
+        } else if ij == player.position {
+            player.direction.glyph()
+        } else {
+            match (moved & vertical > 0, moved & horizontal > 0) {
+                (true, true) => '+',
+                (true, false) => '|',
+                (false, true) => '-',
+                (false, false) => '.',
+            }
+        };
+        result.push(c);
+
+        if ij.1 == field.width - 1 {
+            result.push('\n');
+        }
+    }
+
+    Ok(result)
+}
+
+fn with_obstruction(field: &Field, obstruction: (usize, usize)) -> Field {
+    let mut result = field.clone();
+    result.data[obstruction.0 * field.width + obstruction.1] |= Tile::Blocked as u8;
+    result
+}
+
+pub(crate) struct Walk {
+    // the guard after every step, starting with the initial state.
+    // on a loop the last state repeats the one at cycle_start.
+    pub(crate) states: Vec<Player>,
+    pub(crate) cycle_start: Option<usize>,
+    pub(crate) cycle: Option<LoopTrace>,
+}
+
+// step by step until the guard leaves or repeats a state
+pub(crate) fn walk(player_start: Player, field: &Field) -> Walk {
+    // index into states of the first visit of each (cell, direction)
+    let mut first_visit = vec![usize::MAX; field.data.len() * 4];
+    let state_index =
+        |p: &Player| (p.position.0 * field.width + p.position.1) * 4 + p.direction.index();
+
+    let mut states = vec![player_start];
+    first_visit[state_index(&player_start)] = 0;
+
+    let mut player = player_start;
+    loop {
+        if !step(&mut player, field) {
+            return Walk {
+                states,
+                cycle_start: None,
+                cycle: None,
+            };
+        }
+
+        let k = state_index(&player);
+        if first_visit[k] != usize::MAX {
+            let start = first_visit[k];
+            states.push(player);
+
+            let mut cells = Vec::new();
+            for p in states[start..(states.len() - 1)].iter() {
+                if !cells.contains(&p.position) {
+                    cells.push(p.position);
+                }
+            }
+
+            let cycle = LoopTrace {
+                entry: states[start].position,
+                length: states.len() - 1 - start,
+                cells,
+            };
+
+            return Walk {
+                states,
+                cycle_start: Some(start),
+                cycle: Some(cycle),
+            };
+        }
+
+        first_visit[k] = states.len();
+        states.push(player);
+    }
+}
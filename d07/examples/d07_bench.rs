@@ -0,0 +1,65 @@
+// compares the forward and the backward search on generated calibration lines
+//
+//     cargo run --release -p d07 --example d07_bench -- [lines] [max operands]
+
+use aoc_common::XorShift;
+use d07::SearchMode;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let n_lines = args.next().and_then(|s| s.parse().ok()).unwrap_or(850);
+    let max_operands = args.next().and_then(|s| s.parse().ok()).unwrap_or(12);
+
+    let mut rng = XorShift::new(0x2024_0007);
+
+    // like the puzzle input: about half of the targets are reachable
+    let mut input = String::new();
+    for _ in 0..n_lines {
+        let n = 2 + rng.below(max_operands - 1);
+        let operands = (0..n)
+            .map(|_| 1 + rng.below(999) as u64)
+            .collect::<Vec<_>>();
+
+        let mut target = operands[0];
+        for &b in operands[1..].iter() {
+            target = match rng.below(3) {
+                0 => target.saturating_add(b),
+                1 => target.saturating_mul(b),
+                _ => format!("{}{}", target, b).parse().unwrap_or(u64::MAX),
+            };
+        }
+        if target > u64::MAX / 1000 {
+            target = operands.iter().sum();
+        }
+        if rng.below(2) == 0 {
+            target += 1;
+        }
+
+        let operands = operands
+            .iter()
+            .map(|o| o.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        input += &format!("{}: {}\n", target, operands);
+    }
+
+    println!("lines: {}, operands: 2..={}", n_lines, max_operands);
+
+    let t = std::time::Instant::now();
+    let expected = d07::solve_with(&input, SearchMode::Forward).unwrap();
+    let dt_forward = t.elapsed();
+
+    let t = std::time::Instant::now();
+    let result = d07::solve_with(&input, SearchMode::Backward).unwrap();
+    let dt_backward = t.elapsed();
+
+    assert_eq!(expected, result, "search modes disagree");
+
+    println!("{}", result);
+    println!("Forward:  {:?}", dt_forward);
+    println!("Backward: {:?}", dt_backward);
+    println!(
+        "speedup:  {:.1}x",
+        dt_forward.as_secs_f64() / dt_backward.as_secs_f64()
+    );
+}
@@ -0,0 +1,81 @@
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use crate::{forward, Equation, Operator, Preimage};
+
+// from the last operand back to the first, each operator undone through its inverse.
+// only targets that divide, end in the right digits or are large enough survive, so most
+// branches die right away. visit gets the path in forward order.
+pub(crate) fn search(
+    equation: &Equation,
+    operators: &[Arc<dyn Operator>],
+    overflowed: &mut bool,
+    visit: &mut dyn FnMut(&[usize]) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let mut search = Backward {
+        equation,
+        operators,
+        suffix: Vec::with_capacity(equation.operands.len() - 1),
+        overflowed,
+        visit,
+    };
+    search.from(equation.operands.len(), equation.target)
+}
+
+struct Backward<'a> {
+    equation: &'a Equation,
+    operators: &'a [Arc<dyn Operator>],
+    // operators chosen so far, the last one first
+    suffix: Vec<usize>,
+    // an operand would have had to take a value beyond u64
+    overflowed: &'a mut bool,
+    visit: &'a mut dyn FnMut(&[usize]) -> ControlFlow<()>,
+}
+
+impl Backward<'_> {
+    // the first `end` operands have to evaluate to target
+    fn from(&mut self, end: usize, target: u64) -> ControlFlow<()> {
+        if end == 1 {
+            if self.equation.operands[0] == target {
+                let path = self.suffix.iter().rev().copied().collect::<Vec<_>>();
+                return (self.visit)(&path);
+            }
+            return ControlFlow::Continue(());
+        }
+
+        let b = self.equation.operands[end - 1];
+        for (k, operator) in self.operators.iter().enumerate() {
+            let preimage = operator.inverse(target, b).unwrap_or(Preimage::Nothing);
+
+            self.suffix.push(k);
+            let flow = match preimage {
+                Preimage::Nothing => ControlFlow::Continue(()),
+                Preimage::One(a) => self.from(end - 1, a),
+                Preimage::Any => self.any_prefix(end - 1),
+                Preimage::Overflow => {
+                    *self.overflowed = true;
+                    ControlFlow::Continue(())
+                }
+            };
+            self.suffix.pop();
+            flow?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    // e.g. x * 0 = 0, the first `end` operands may evaluate to anything
+    fn any_prefix(&mut self, end: usize) -> ControlFlow<()> {
+        let (equation, operators) = (self.equation, self.operators);
+        let suffix = self.suffix.iter().rev().copied().collect::<Vec<_>>();
+        let (overflowed, visit) = (&mut *self.overflowed, &mut *self.visit);
+
+        let mut path = Vec::with_capacity(equation.operands.len() - 1);
+        forward(equation, operators, end, None, overflowed, &mut |prefix| {
+            path.clear();
+            path.extend_from_slice(prefix);
+            path.extend_from_slice(&suffix);
+            visit(&path)
+        })
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::{Equation, Operator, OperatorSet, Reach, SearchMode};
+
+#[derive(Debug, Clone)]
+pub struct Calibration {
+    // position of the line in the input, starting at 0
+    pub line: usize,
+    pub equation: Equation,
+    // 1 if + and * are enough, 2 if it takes concatenation
+    pub part: usize,
+    // the first solution found
+    pub operators: Vec<Arc<dyn Operator>>,
+    // every solution with the operators of the part, only when asked for
+    pub solutions: Option<Vec<Vec<Arc<dyn Operator>>>>,
+}
+
+// one entry per equation that can be made true
+pub fn calibrate(input: &str, all: bool) -> Result<Vec<Calibration>, aoc_common::AocError> {
+    let parts = [(1, OperatorSet::part_1()), (2, OperatorSet::part_2())];
+    let mut result = Vec::new();
+
+    for (line, s) in input.lines().enumerate() {
+        if s.trim().is_empty() {
+            continue;
+        }
+        let equation = Equation::parse(s)?;
+
+        let mut found = None;
+        for (part, operators) in parts.iter() {
+            match equation.reach(operators, SearchMode::Backward) {
+                Reach::Reachable => {
+                    found = Some((*part, operators, equation.solve(operators).unwrap()));
+                    break;
+                }
+                Reach::Unreachable => (),
+                Reach::Overflow => return Err(crate::overflow_error(line)),
+            }
+        }
+
+        if let Some((part, operators, first)) = found {
+            result.push(Calibration {
+                line,
+                solutions: all.then(|| equation.solutions(operators)),
+                equation,
+                part,
+                operators: first,
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn explain(input: &str) -> aoc_common::AocResult {
+    let mut out = String::new();
+
+    for c in calibrate(input, true)? {
+        let solutions = c.solutions.unwrap_or_default();
+
+        writeln!(
+            out,
+            "line {}, part {}: {} = {}",
+            c.line + 1,
+            c.part,
+            c.equation.target,
+            c.equation.expression(&c.operators)
+        )
+        .unwrap();
+
+        if solutions.len() > 1 {
+            writeln!(out, "    {} solutions:", solutions.len()).unwrap();
+            for operators in solutions.iter() {
+                writeln!(out, "    {}", c.equation.expression(operators)).unwrap();
+            }
+        }
+    }
+
+    write!(out, "{}", crate::solve(input)?).unwrap();
+    Ok(out)
+}
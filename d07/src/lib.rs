@@ -1,108 +1,304 @@
+mod backward;
+mod explain;
+mod operator;
+
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+
+pub use explain::{calibrate, explain, Calibration};
+pub use operator::{
+    Add, Concatenate, Multiply, Operator, OperatorSet, Outcome, Power, Preimage, Subtract,
+};
+
 pub fn solve(input: &str) -> aoc_common::AocResult {
-    let mut s1 = 0;
-    let mut s2 = 0;
+    solve_with(input, SearchMode::Backward)
+}
 
-    let mut operands = Vec::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    // from the first operand towards the target
+    Forward,
+    // from the target back to the first operand through the inverse operators,
+    // forward for operator sets that can not be inverted
+    Backward,
+}
 
-    for line in input.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
-        let (r1, r2) = check_line(line, &mut operands)?;
-        s1 += r1;
-        s2 += r2;
+pub fn solve_with(input: &str, mode: SearchMode) -> aoc_common::AocResult {
+    let part_1 = OperatorSet::part_1();
+    let part_2 = OperatorSet::part_2();
+
+    let equations = parse_input(input)?;
+
+    let sums = equations
+        .par_iter()
+        .map(|(line, equation)| {
+            let target = equation.target as u128;
+            Ok(match reached(*line, equation.reach(&part_1, mode))? {
+                true => (target, target),
+                false => match reached(*line, equation.reach(&part_2, mode))? {
+                    true => (0, target),
+                    false => (0, 0),
+                },
+            })
+        })
+        .collect::<Vec<Result<(u128, u128), aoc_common::AocError>>>();
+
+    // the first failing line in input order, not whichever thread finished first
+    let (mut s1, mut s2) = (0, 0);
+    for r in sums {
+        let (a, b) = r?;
+        s1 += a;
+        s2 += b;
     }
 
     Ok(format!("d07/01 = {}, d07/02 = {}", s1, s2))
 }
 
-// (delta 1, delta 2)
-fn check_line(
-    line: &str,
-    operands: &mut Vec<(u64, bool)>,
-) -> Result<(u64, u64), aoc_common::AocError> {
-    operands.clear();
-
-    let [result_s, operands_s] =
-        aoc_common::split_to_array::<2>(line, ": ").ok_or(aoc_common::AocError::InvalidInput)?;
-
-    for operand_s in operands_s
-        .split_whitespace()
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-    {
-        operands.push((operand_s.parse::<u64>()?, false));
+// one sum for a custom operator set, e.g. "+,*,-"
+pub fn solve_operators(input: &str, list: &str) -> aoc_common::AocResult {
+    let operators = OperatorSet::parse(list)?;
+
+    let targets = parse_input(input)?
+        .par_iter()
+        .map(|(line, equation)| {
+            let reach = equation.reach(&operators, SearchMode::Backward);
+            Ok(if reached(*line, reach)? {
+                equation.target as u128
+            } else {
+                0
+            })
+        })
+        .collect::<Vec<Result<u128, aoc_common::AocError>>>();
+
+    let mut sum = 0;
+    for r in targets {
+        sum += r?;
+    }
+
+    Ok(format!("d07/[{}] = {}", operators, sum))
+}
+
+// an overflow is an error, guessing either way could give a wrong sum
+fn reached(line: usize, reach: Reach) -> Result<bool, aoc_common::AocError> {
+    match reach {
+        Reach::Reachable => Ok(true),
+        Reach::Unreachable => Ok(false),
+        Reach::Overflow => Err(overflow_error(line)),
     }
+}
+
+pub(crate) fn overflow_error(line: usize) -> aoc_common::AocError {
+    aoc_common::AocError::InvalidInputReason(format!(
+        "line {}: an intermediate result does not fit a u64",
+        line + 1
+    ))
+}
+
+// with the position of each line in the input, starting at 0
+fn parse_input(input: &str) -> Result<Vec<(usize, Equation)>, aoc_common::AocError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(line, l)| Ok((line, Equation::parse(l)?)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reach {
+    Reachable,
+    Unreachable,
+    // no solution within u64, but some branch left the range before it could be ruled out
+    Overflow,
+}
 
-    for i in 0..operands.len() {
-        if i == 0 {
-            operands[i].1 = operands[i].0 == 0;
-            continue;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub target: u64,
+    pub operands: Vec<u64>,
+}
+
+impl Equation {
+    pub fn parse(line: &str) -> Result<Self, aoc_common::AocError> {
+        let [target_s, operands_s] = aoc_common::split_to_array::<2>(line.trim(), ": ")
+            .ok_or(aoc_common::AocError::InvalidInput)?;
+
+        let mut operands = Vec::new();
+        for operand_s in operands_s.split_whitespace() {
+            operands.push(operand_s.parse::<u64>()?);
         }
 
-        operands[i].1 = operands[i - 1].1;
-        operands[i].1 |= operands[i].0 == 0;
+        if operands.is_empty() {
+            return Err(aoc_common::AocError::InvalidInput);
+        }
+
+        Ok(Self {
+            target: target_s.parse::<u64>()?,
+            operands,
+        })
     }
 
-    let result = result_s.parse::<u64>()?;
+    // the backward search only reports an overflow where the operand before it would have to
+    // take a value beyond u64, the forward search wherever it can not prune an overflowing
+    // branch, so it may report Overflow where Backward proves Unreachable
+    pub fn reach(&self, operators: &OperatorSet, mode: SearchMode) -> Reach {
+        let mut stop = |_: &[usize]| ControlFlow::Break(());
+        let mut overflowed = false;
+        let list = operators.operators();
+
+        let flow = if mode == SearchMode::Backward && self.invertible(operators) {
+            backward::search(self, list, &mut overflowed, &mut stop)
+        } else {
+            forward(
+                self,
+                list,
+                self.operands.len(),
+                Some(self.target),
+                &mut overflowed,
+                &mut stop,
+            )
+        };
 
-    let (r, used_concat) = check_result(0, 0, false, result, operands);
-    if r == result && !used_concat {
-        Ok((result, result))
-    } else if r == result && used_concat {
-        Ok((0, result))
-    } else {
-        Ok((0, 0))
+        match (flow.is_break(), overflowed) {
+            (true, _) => Reach::Reachable,
+            (false, true) => Reach::Overflow,
+            (false, false) => Reach::Unreachable,
+        }
     }
-}
 
-fn check_result(
-    depth: usize,
-    accumulator: u64,
-    used_concat: bool,
-    target: u64,
-    operands: &[(u64, bool)],
-) -> (u64, bool) {
-    // 305235895
-    if operands.is_empty() {
-        return (accumulator, used_concat);
+    // operators report whether they have an inverse on every call, asking once is enough
+    fn invertible(&self, operators: &OperatorSet) -> bool {
+        let last = *self.operands.last().unwrap();
+        operators
+            .operators()
+            .iter()
+            .all(|o| o.inverse(self.target, last).is_some())
     }
 
-    if accumulator > target {
-        return (target.wrapping_add(1), false);
+    // the first operator sequence that reaches the target, evaluated left to right
+    pub fn solve(&self, operators: &OperatorSet) -> Option<Vec<Arc<dyn Operator>>> {
+        let mut result = None;
+        self.search(operators, &mut |path| {
+            result = Some(resolve(operators, path));
+            ControlFlow::Break(())
+        });
+        result
     }
 
-    if depth == 0 {
-        return check_result(depth + 1, operands[0].0, false, target, &operands[1..]);
+    // every operator sequence that reaches the target
+    pub fn solutions(&self, operators: &OperatorSet) -> Vec<Vec<Arc<dyn Operator>>> {
+        let mut result = Vec::new();
+        self.search(operators, &mut |path| {
+            result.push(resolve(operators, path));
+            ControlFlow::Continue(())
+        });
+        result
     }
 
-    let v1 = accumulator.wrapping_add(operands[0].0);
-    let (r1, c1) = check_result(depth + 1, v1, used_concat, target, &operands[1..]);
-    if r1 == target && !c1 {
-        return (r1, c1);
+    // e.g. 81 + 40 * 27
+    pub fn expression(&self, operators: &[Arc<dyn Operator>]) -> String {
+        let mut result = self.operands[0].to_string();
+        for (operator, operand) in operators.iter().zip(self.operands[1..].iter()) {
+            result += &format!(" {} {}", operator.symbol(), operand);
+        }
+        result
     }
 
-    let v2 = accumulator.wrapping_mul(operands[0].0);
-    let (r2, c2) = check_result(depth + 1, v2, used_concat, target, &operands[1..]);
-    if r2 == target && !c2 {
-        return (r2, c2);
+    // path holds indices into the operator set, overflowing branches are skipped
+    fn search(&self, operators: &OperatorSet, visit: &mut dyn FnMut(&[usize]) -> ControlFlow<()>) {
+        let _ = forward(
+            self,
+            operators.operators(),
+            self.operands.len(),
+            Some(self.target),
+            &mut false,
+            visit,
+        );
     }
+}
+
+fn resolve(operators: &OperatorSet, path: &[usize]) -> Vec<Arc<dyn Operator>> {
+    path.iter()
+        .map(|&k| operators.operators()[k].clone())
+        .collect()
+}
 
-    let v3 = concatenate(accumulator, operands[0].0);
-    let (r3, c3) = check_result(depth + 1, v3, true, target, &operands[1..]);
+// every operator sequence for the first `end` operands that evaluates to target, or to
+// anything at all without one. overflowed is set if a branch left the u64 range where the
+// operators after it could still bring it back to the target
+fn forward(
+    equation: &Equation,
+    operators: &[Arc<dyn Operator>],
+    end: usize,
+    target: Option<u64>,
+    overflowed: &mut bool,
+    visit: &mut dyn FnMut(&[usize]) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let operands = &equation.operands[..end];
 
-    if r1 == target {
-        (r1, c1)
-    } else if r2 == target {
-        (r2, c2)
-    } else {
-        (r3, c3)
+    // monotone[d]: no operator can make the accumulator smaller from operand d on,
+    // so an accumulator above the target is a dead end
+    let mut monotone = vec![true; operands.len() + 1];
+    for d in (1..operands.len()).rev() {
+        monotone[d] = monotone[d + 1] && operators.iter().all(|o| o.never_decreases(operands[d]));
     }
+
+    let mut search = Search {
+        operands,
+        target,
+        operators,
+        monotone: &monotone,
+        path: Vec::with_capacity(operands.len().saturating_sub(1)),
+        overflowed,
+        visit,
+    };
+    search.from(1, operands[0])
+}
+
+struct Search<'a> {
+    operands: &'a [u64],
+    target: Option<u64>,
+    operators: &'a [Arc<dyn Operator>],
+    monotone: &'a [bool],
+    path: Vec<usize>,
+    overflowed: &'a mut bool,
+    visit: &'a mut dyn FnMut(&[usize]) -> ControlFlow<()>,
 }
 
-fn concatenate(a: u64, b: u64) -> u64 {
-    let mut a2 = a;
-    let mut b2 = b;
-    while b2 > 0 {
-        a2 = a2.wrapping_mul(10);
-        b2 /= 10;
+impl Search<'_> {
+    fn from(&mut self, depth: usize, accumulator: u64) -> ControlFlow<()> {
+        if depth == self.operands.len() {
+            if self.target.is_none_or(|t| t == accumulator) {
+                return (self.visit)(&self.path);
+            }
+            return ControlFlow::Continue(());
+        }
+
+        if self.target.is_some_and(|t| accumulator > t) && self.monotone[depth] {
+            return ControlFlow::Continue(());
+        }
+
+        for (k, operator) in self.operators.iter().enumerate() {
+            let next = match operator.apply(accumulator, self.operands[depth]) {
+                Outcome::Value(next) => next,
+                Outcome::Undefined => continue,
+                Outcome::Overflow => {
+                    // above every target, a dead end if nothing after it decreases
+                    if self.target.is_none() || !self.monotone[depth + 1] {
+                        *self.overflowed = true;
+                    }
+                    continue;
+                }
+            };
+
+            self.path.push(k);
+            let flow = self.from(depth + 1, next);
+            self.path.pop();
+            flow?;
+        }
+
+        ControlFlow::Continue(())
     }
-    a2 + b
 }
@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+pub trait Operator: std::fmt::Debug + Send + Sync {
+    // as written in expressions and operator lists
+    fn symbol(&self) -> String;
+
+    fn apply(&self, a: u64, b: u64) -> Outcome;
+
+    // every a with apply(a, b) == result, None if the operator can not be inverted
+    fn inverse(&self, _result: u64, _b: u64) -> Option<Preimage> {
+        None
+    }
+
+    // true if apply(a, b) >= a for every a, so an accumulator above the target stays above it
+    fn never_decreases(&self, _b: u64) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Value(u64),
+    // e.g. below zero
+    Undefined,
+    // the result does not fit a u64
+    Overflow,
+}
+
+impl Outcome {
+    // checked arithmetic returns None on overflow
+    fn checked(value: Option<u64>) -> Self {
+        value.map_or(Self::Overflow, Self::Value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preimage {
+    Nothing,
+    One(u64),
+    // every a works, e.g. a * 0 = 0
+    Any,
+    // the only a does not fit a u64
+    Overflow,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Add;
+
+impl Operator for Add {
+    fn symbol(&self) -> String {
+        "+".to_string()
+    }
+
+    fn apply(&self, a: u64, b: u64) -> Outcome {
+        Outcome::checked(a.checked_add(b))
+    }
+
+    fn inverse(&self, result: u64, b: u64) -> Option<Preimage> {
+        Some(
+            result
+                .checked_sub(b)
+                .map_or(Preimage::Nothing, Preimage::One),
+        )
+    }
+
+    fn never_decreases(&self, _b: u64) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Multiply;
+
+impl Operator for Multiply {
+    fn symbol(&self) -> String {
+        "*".to_string()
+    }
+
+    fn apply(&self, a: u64, b: u64) -> Outcome {
+        Outcome::checked(a.checked_mul(b))
+    }
+
+    fn inverse(&self, result: u64, b: u64) -> Option<Preimage> {
+        Some(match (result, b) {
+            (0, 0) => Preimage::Any,
+            (_, 0) => Preimage::Nothing,
+            _ if result.is_multiple_of(b) => Preimage::One(result / b),
+            _ => Preimage::Nothing,
+        })
+    }
+
+    fn never_decreases(&self, b: u64) -> bool {
+        b != 0
+    }
+}
+
+// the digits of b appended to those of a, in the given base
+#[derive(Debug, Clone, Copy)]
+pub struct Concatenate {
+    pub base: u64,
+}
+
+impl Concatenate {
+    // base to the number of digits of b, None if that does not fit
+    fn shift(&self, b: u64) -> Option<u64> {
+        let mut shift = self.base;
+        let mut rest = b / self.base;
+        while rest > 0 {
+            shift = shift.checked_mul(self.base)?;
+            rest /= self.base;
+        }
+        Some(shift)
+    }
+}
+
+impl Operator for Concatenate {
+    fn symbol(&self) -> String {
+        match self.base {
+            10 => "||".to_string(),
+            base => format!("||{}", base),
+        }
+    }
+
+    fn apply(&self, a: u64, b: u64) -> Outcome {
+        match self.shift(b) {
+            Some(shift) => Outcome::checked(a.checked_mul(shift).and_then(|s| s.checked_add(b))),
+            // 0 || b is b even when the shift does not fit
+            None if a == 0 => Outcome::Value(b),
+            None => Outcome::Overflow,
+        }
+    }
+
+    fn inverse(&self, result: u64, b: u64) -> Option<Preimage> {
+        Some(match (result.checked_sub(b), self.shift(b)) {
+            (Some(rest), Some(shift)) if rest.is_multiple_of(shift) => Preimage::One(rest / shift),
+            (Some(0), None) => Preimage::One(0),
+            _ => Preimage::Nothing,
+        })
+    }
+
+    fn never_decreases(&self, _b: u64) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Subtract;
+
+impl Operator for Subtract {
+    fn symbol(&self) -> String {
+        "-".to_string()
+    }
+
+    fn apply(&self, a: u64, b: u64) -> Outcome {
+        a.checked_sub(b).map_or(Outcome::Undefined, Outcome::Value)
+    }
+
+    fn inverse(&self, result: u64, b: u64) -> Option<Preimage> {
+        Some(
+            result
+                .checked_add(b)
+                .map_or(Preimage::Overflow, Preimage::One),
+        )
+    }
+
+    fn never_decreases(&self, b: u64) -> bool {
+        b == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Power;
+
+impl Operator for Power {
+    fn symbol(&self) -> String {
+        "^".to_string()
+    }
+
+    fn apply(&self, a: u64, b: u64) -> Outcome {
+        match (a, b) {
+            (_, 0) => Outcome::Value(1),
+            (0 | 1, _) => Outcome::Value(a),
+            _ => Outcome::checked(u32::try_from(b).ok().and_then(|b| a.checked_pow(b))),
+        }
+    }
+
+    fn inverse(&self, result: u64, b: u64) -> Option<Preimage> {
+        Some(match (result, b) {
+            (1, 0) => Preimage::Any,
+            (_, 0) => Preimage::Nothing,
+            // their own roots, even for exponents that do not fit a u32
+            (0 | 1, _) => Preimage::One(result),
+            _ => match u32::try_from(b).ok().and_then(|b| integer_root(result, b)) {
+                Some(a) => Preimage::One(a),
+                None => Preimage::Nothing,
+            },
+        })
+    }
+
+    fn never_decreases(&self, b: u64) -> bool {
+        b != 0
+    }
+}
+
+// a with a^n == x, by bisection since a float estimate is off beyond 2^53
+fn integer_root(x: u64, n: u32) -> Option<u64> {
+    if n == 1 {
+        return Some(x);
+    }
+
+    // lo^n <= x < hi^n, with n >= 2 every root is below 2^32
+    let (mut lo, mut hi) = (0u64, 1u64 << 32);
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        match mid.checked_pow(n) {
+            Some(p) if p <= x => lo = mid,
+            _ => hi = mid,
+        }
+    }
+
+    (lo.checked_pow(n) == Some(x)).then_some(lo)
+}
+
+// the operators an equation may use, tried in this order
+#[derive(Debug, Clone, Default)]
+pub struct OperatorSet {
+    operators: Vec<Arc<dyn Operator>>,
+}
+
+impl OperatorSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn part_1() -> Self {
+        Self::parse("+,*").unwrap()
+    }
+
+    pub fn part_2() -> Self {
+        Self::parse("+,*,||").unwrap()
+    }
+
+    // built in symbols separated by commas or whitespace: + * - ^ || and ||<base>
+    pub fn parse(list: &str) -> Result<Self, aoc_common::AocError> {
+        let mut result = Self::new();
+
+        for symbol in list
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+        {
+            let operator = builtin(symbol).ok_or_else(|| {
+                aoc_common::AocError::InvalidInputReason(format!("unknown operator {}", symbol))
+            })?;
+            result.register(operator);
+        }
+
+        if result.operators.is_empty() {
+            return Err(aoc_common::AocError::InvalidInputReason(
+                "empty operator list".to_string(),
+            ));
+        }
+
+        Ok(result)
+    }
+
+    pub fn register(&mut self, operator: Arc<dyn Operator>) {
+        self.operators.push(operator);
+    }
+
+    pub fn operators(&self) -> &[Arc<dyn Operator>] {
+        &self.operators
+    }
+}
+
+impl std::fmt::Display for OperatorSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let symbols = self
+            .operators
+            .iter()
+            .map(|o| o.symbol())
+            .collect::<Vec<_>>();
+        write!(f, "{}", symbols.join(","))
+    }
+}
+
+fn builtin(symbol: &str) -> Option<Arc<dyn Operator>> {
+    Some(match symbol {
+        "+" => Arc::new(Add),
+        "*" => Arc::new(Multiply),
+        "-" => Arc::new(Subtract),
+        "^" => Arc::new(Power),
+        "||" => Arc::new(Concatenate { base: 10 }),
+        _ => {
+            let base = symbol.strip_prefix("||")?.parse::<u64>().ok()?;
+            if base < 2 {
+                return None;
+            }
+            Arc::new(Concatenate { base })
+        }
+    })
+}
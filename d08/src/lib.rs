@@ -1,75 +1,163 @@
+mod render;
+
+use std::sync::Arc;
+
 use rustc_hash::FxHashSet as HashSet;
 
+pub use render::{explain, frequency_counts, render};
+
 pub fn solve(input: &str) -> aoc_common::AocResult {
     let antennas = parse_input(input)?;
 
-    let s1 = count_antinodes(&antennas);
-    let s2 = count_harmonic_antinodes(&antennas);
+    let s1 = count_set(&antennas, &AntinodeModel::part_1());
+    let s2 = count_set(&antennas, &AntinodeModel::part_2());
 
     Ok(format!("d08/01 = {}, d08/02 = {}", s1, s2))
 }
 
-fn count_antinodes(antennas: &Antennas) -> usize {
-    count_set(antennas, antinodes_iter)
+// unique antinode locations on the map under a custom model
+pub fn count_antinodes(input: &str, model: &AntinodeModel) -> Result<usize, aoc_common::AocError> {
+    Ok(count_set(&parse_input(input)?, model))
 }
 
-fn count_harmonic_antinodes(antennas: &Antennas) -> usize {
-    count_set(antennas, line)
+// whether antennas of two frequencies create antinodes together, asked both ways round
+pub type Compatibility = Arc<dyn Fn(char, char) -> bool + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ratio {
+    // one antenna k times as far away as the other
+    Times(u32),
+    // every grid point in line with both antennas
+    InLine,
 }
 
-fn count_set<F, G>(antennas: &Antennas, iter_func: F) -> usize
-where
-    F: Fn(Point, Point, usize, usize) -> G,
-    G: Iterator<Item = Point>,
-{
-    let mut antinodes = HashSet::default();
+#[derive(Clone)]
+pub struct AntinodeModel {
+    pub ratio: Ratio,
+    // also the points between the two antennas, where they fall on the grid
+    pub inner: bool,
+    // antinodes beyond each antenna, repeated at the spacing of the first one,
+    // None up to the edge of the map
+    pub harmonics: Option<usize>,
+    pub compatible: Compatibility,
+}
+
+impl AntinodeModel {
+    pub fn part_1() -> Self {
+        Self {
+            ratio: Ratio::Times(2),
+            inner: true,
+            harmonics: Some(1),
+            compatible: Self::same_frequency(),
+        }
+    }
+
+    pub fn part_2() -> Self {
+        Self {
+            ratio: Ratio::InLine,
+            inner: true,
+            harmonics: None,
+            compatible: Self::same_frequency(),
+        }
+    }
+
+    pub fn same_frequency() -> Compatibility {
+        Arc::new(|a, b| a == b)
+    }
 
-    for frequency in antennas.coordinates.iter() {
-        for i in 0..frequency.len() {
-            for j in (i + 1)..frequency.len() {
-                antinodes.extend(iter_func(
-                    frequency[i],
-                    frequency[j],
-                    antennas.width,
-                    antennas.height,
-                ));
+    fn antinodes(&self, a: Point, b: Point, width: usize, height: usize) -> Vec<Point> {
+        let delta = b - a;
+        let mut result = Vec::new();
+
+        // p = a + t * delta with |p - a| = k * |p - b|:
+        // t = k / (k + 1) and 1 / (k + 1) between the antennas,
+        // t = 1 + 1 / (k - 1) and -1 / (k - 1) outside
+        let spacing = match self.ratio {
+            Ratio::Times(k) => {
+                let k = k as i64;
+                if self.inner && divides(k + 1, delta) {
+                    let d = delta / (k + 1);
+                    result.extend([a + d, b - d]);
+                }
+                (k > 1 && divides(k - 1, delta)).then(|| delta / (k - 1))
+            }
+            Ratio::InLine => {
+                let n = gcd(delta.x, delta.y);
+                let step = delta / n;
+                // the antennas themselves are in line, the points between only if asked for
+                let between = if self.inner { 1..n } else { 1..1 };
+                result.push(a);
+                result.extend(between.map(|s| a + step * s));
+                result.push(b);
+                Some(step)
+            }
+        };
+
+        if let Some(spacing) = spacing {
+            let limit = self.harmonics.unwrap_or(usize::MAX);
+            for (start, direction) in [(b, spacing), (a, spacing * -1)] {
+                result.extend(
+                    (1i64..)
+                        .map(|n| start + direction * n)
+                        .take_while(|p| inside(*p, width, height))
+                        .take(limit),
+                );
             }
         }
+
+        result
     }
+}
 
-    antinodes.len()
+impl std::fmt::Debug for AntinodeModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("AntinodeModel")
+            .field("ratio", &self.ratio)
+            .field("inner", &self.inner)
+            .field("harmonics", &self.harmonics)
+            .finish_non_exhaustive()
+    }
 }
 
-fn antinodes_iter(
-    p1: Point,
-    p2: Point,
-    width: usize,
-    height: usize,
-) -> impl Iterator<Item = Point> {
-    line(p1, p2, width, height).filter(move |p| {
-        let d1 = p.dist_sq(p1);
-        let d2 = p.dist_sq(p2);
-        (4 * d1 == d2) || (4 * d2 == d1)
-    })
+fn count_set(antennas: &Antennas, model: &AntinodeModel) -> usize {
+    let mut antinodes = HashSet::default();
+    for_each_pair(antennas, model, |_, _, points| antinodes.extend(points));
+    antinodes.len()
 }
 
-fn line(p1: Point, p2: Point, width: usize, height: usize) -> impl Iterator<Item = Point> {
-    let delta = p2 - p1;
-    let step = delta / gcd(delta.x, delta.y);
+// the antinodes of every compatible pair of antennas, with the indices of both frequencies
+fn for_each_pair<F>(antennas: &Antennas, model: &AntinodeModel, mut visit: F)
+where
+    F: FnMut(usize, usize, Vec<Point>),
+{
+    for (f, frequency) in antennas.coordinates.iter().enumerate() {
+        for (g, other) in antennas.coordinates.iter().enumerate().skip(f) {
+            let (a, b) = (antennas.names[f], antennas.names[g]);
+            if !(model.compatible)(a, b) && !(model.compatible)(b, a) {
+                continue;
+            }
 
-    fn inside(p: Point, width: usize, height: usize) -> bool {
-        p.x >= 0 && p.x < (width as i64) && p.y >= 0 && p.y < (height as i64)
+            for (i, &p1) in frequency.iter().enumerate() {
+                // within one frequency every pair once
+                let first = if f == g { i + 1 } else { 0 };
+                for &p2 in other[first..].iter() {
+                    visit(
+                        f,
+                        g,
+                        model.antinodes(p1, p2, antennas.width, antennas.height),
+                    );
+                }
+            }
+        }
     }
+}
 
-    let first_half = (0i64..)
-        .map(move |d| p1 + step * d)
-        .take_while(move |p| inside(*p, width, height));
-
-    let second_half = (1i64..)
-        .map(move |d| p1 - step * d)
-        .take_while(move |p| inside(*p, width, height));
+fn inside(p: Point, width: usize, height: usize) -> bool {
+    p.x >= 0 && p.x < (width as i64) && p.y >= 0 && p.y < (height as i64)
+}
 
-    first_half.chain(second_half)
+fn divides(n: i64, p: Point) -> bool {
+    p.x % n == 0 && p.y % n == 0
 }
 
 fn parse_input(input: &str) -> Result<Antennas, aoc_common::AocError> {
@@ -151,14 +239,6 @@ impl Point {
     fn new(x: i64, y: i64) -> Self {
         Self { x, y }
     }
-
-    fn abs_sq(&self) -> i64 {
-        self.x * self.x + self.y * self.y
-    }
-
-    fn dist_sq(&self, p: Point) -> i64 {
-        (*self - p).abs_sq()
-    }
 }
 
 impl std::ops::Add<Point> for Point {
@@ -207,7 +287,7 @@ impl std::ops::Div<i64> for Point {
 
 fn gcd(a: i64, b: i64) -> i64 {
     if a == 0 || b == 0 {
-        return 1;
+        return a.abs().max(b.abs());
     }
 
     let mut a = a.abs();
@@ -0,0 +1,141 @@
+use std::fmt::Write;
+
+use rustc_hash::FxHashSet as HashSet;
+
+use crate::{for_each_pair, parse_input, Antennas, AntinodeModel, Point};
+
+const ANTINODE_COLOR: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+// both parts as in the puzzle illustrations, for the runner's verbose mode
+pub fn explain(input: &str) -> aoc_common::AocResult {
+    let antennas = parse_input(input)?;
+    let positions = antennas
+        .coordinates
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<HashSet<_>>();
+
+    let mut out = String::new();
+    for (part, model) in [(1, AntinodeModel::part_1()), (2, AntinodeModel::part_2())] {
+        let sets = per_frequency(&antennas, &model);
+
+        writeln!(out, "part {}:", part).unwrap();
+        write!(out, "{}", draw(&antennas, &sets, None, false)).unwrap();
+        for (name, set) in antennas.names.iter().zip(sets.iter()) {
+            let on_antennas = set.intersection(&positions).count();
+            writeln!(
+                out,
+                "    {}: {} locations, {} on an antenna",
+                name,
+                set.len(),
+                on_antennas
+            )
+            .unwrap();
+        }
+    }
+
+    write!(out, "{}", crate::solve(input)?).unwrap();
+    Ok(out)
+}
+
+// antennas as their frequency, antinodes as #. an antinode on an antenna keeps the antenna
+// like the puzzle does, with color it is highlighted like the other antinodes.
+// frequencies limits the map to those antennas and the antinodes they take part in.
+pub fn render(
+    input: &str,
+    model: &AntinodeModel,
+    frequencies: Option<&[char]>,
+    color: bool,
+) -> Result<String, aoc_common::AocError> {
+    let antennas = parse_input(input)?;
+
+    if let Some(c) = frequencies
+        .unwrap_or_default()
+        .iter()
+        .find(|c| !antennas.names.contains(c))
+    {
+        return Err(aoc_common::AocError::InvalidInputReason(format!(
+            "no antenna with frequency {}",
+            c
+        )));
+    }
+
+    let sets = per_frequency(&antennas, model);
+    Ok(draw(&antennas, &sets, frequencies, color))
+}
+
+// unique antinode locations per frequency, in order of first appearance. an antinode of two
+// compatible frequencies counts for both.
+pub fn frequency_counts(
+    input: &str,
+    model: &AntinodeModel,
+) -> Result<Vec<(char, usize)>, aoc_common::AocError> {
+    let antennas = parse_input(input)?;
+    let sets = per_frequency(&antennas, model);
+
+    Ok(antennas
+        .names
+        .iter()
+        .zip(sets.iter())
+        .map(|(&name, set)| (name, set.len()))
+        .collect())
+}
+
+fn per_frequency(antennas: &Antennas, model: &AntinodeModel) -> Vec<HashSet<Point>> {
+    let mut sets = vec![HashSet::default(); antennas.names.len()];
+
+    for_each_pair(antennas, model, |f, g, points| {
+        if f != g {
+            sets[g].extend(points.iter().copied());
+        }
+        sets[f].extend(points);
+    });
+
+    sets
+}
+
+fn draw(
+    antennas: &Antennas,
+    sets: &[HashSet<Point>],
+    frequencies: Option<&[char]>,
+    color: bool,
+) -> String {
+    let shown = |name: &char| frequencies.is_none_or(|f| f.contains(name));
+
+    let mut antinodes = HashSet::default();
+    let mut cells = vec![None; antennas.width * antennas.height];
+
+    for (f, name) in antennas.names.iter().enumerate().filter(|(_, n)| shown(n)) {
+        antinodes.extend(sets[f].iter().copied());
+        for p in antennas.coordinates[f].iter() {
+            cells[index(antennas, *p)] = Some(*name);
+        }
+    }
+
+    let mut result = String::with_capacity((antennas.width + 1) * antennas.height);
+    for y in (0..antennas.height as i64).rev() {
+        for x in 0..antennas.width as i64 {
+            let p = Point::new(x, y);
+            let c = cells[index(antennas, p)];
+
+            match (c, antinodes.contains(&p)) {
+                (c, true) if color => {
+                    write!(result, "{}{}{}", ANTINODE_COLOR, c.unwrap_or('#'), RESET).unwrap()
+                }
+                (Some(c), _) => result.push(c),
+                (None, true) => result.push('#'),
+                (None, false) => result.push('.'),
+            }
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+// y counts up from the last row
+fn index(antennas: &Antennas, p: Point) -> usize {
+    (antennas.height - 1 - p.y as usize) * antennas.width + p.x as usize
+}
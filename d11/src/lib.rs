@@ -47,7 +47,7 @@ fn blink_stone(stone: u64) -> (u64, Option<u64>) {
     }
 
     let digits = n_digits(stone);
-    if digits % 2 == 0 {
+    if digits.is_multiple_of(2) {
         let (a, b) = split(stone, digits);
         (a, Some(b))
     } else {